@@ -1,12 +1,14 @@
 /*
-The crate [operaton_task_worker_macros] provides a proc-macro attribute macro to register an external task handler function with a name (activityId/topic).
+The crate [operaton_task_worker_macros] provides a proc-macro attribute macro to register an external task handler function with a name (activityId/topic),
+and derive macros to convert between process variables and structs.
 */
 
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, ItemFn, Meta, Expr, Lit};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericArgument, ItemFn, Meta, MetaNameValue, Expr, Lit, PathArguments, Token, Type};
 use proc_macro_crate::{crate_name, FoundCrate};
 
 /// Attribute macro to register an external task handler function with a name (activityId/topic).
@@ -21,32 +23,71 @@ use proc_macro_crate::{crate_name, FoundCrate};
 ///     Ok(std::collections::HashMap::new())
 /// }
 /// ```
+///
+/// The handler may take the [`TaskContext`] of the task as second argument, e.g. to read the tenant id:
+///
+/// ```ignore
+/// #[task_handler(name = "example_tenant")]
+/// fn tenant(_input: &InputVariables, context: &TaskContext) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+///     log::info!("Handling task for tenant {:?}", context.tenant_id());
+///     Ok(std::collections::HashMap::new())
+/// }
+/// ```
+///
+/// Supported arguments:
+/// - `name = "..."` (required): the activityId of the Service Task, also used as topic unless `topic` is given
+/// - `topic = "..."`: the topic the worker subscribes to for this handler
+/// - `lock_duration = 120000`: lock duration in milliseconds for this topic (overrides the configured default)
+/// - `variables = ["a", "b"]`: only fetch these variables instead of all variables visible to the task
+/// - `local_variables = true`: only fetch the local variables of the task's execution
+/// - `max_retries = 3`, `retry_backoff = "exponential"`, `retry_delay = 1000`, `retry_max_delay = 60000`:
+///   override the configured retry policy for technical failures of this handler
+/// - `process_definition_keys = ["Invoice"]`, `process_definition_version_tag = "1.0"`, `business_key = "..."`:
+///   only handle tasks of these process definitions, version tag or business key
+/// - `process_variables(region = "EU", priority = 1)`: only fetch tasks of process instances with these variable values
 #[proc_macro_attribute]
 pub fn task_handler(attr: TokenStream, item: TokenStream) -> TokenStream {
-    // Accept a single name-value meta: name = "..."
-    let meta = parse_macro_input!(attr as Meta);
+    // Accept a comma separated list of name-value metas: name = "...", topic = "...", ...
+    let metas = parse_macro_input!(attr with Punctuated::<Meta, Token![,]>::parse_terminated);
     let input_fn = parse_macro_input!(item as ItemFn);
 
-    let name_value = match meta {
-        Meta::NameValue(nv) if nv.path.is_ident("name") => match nv.value {
-            Expr::Lit(expr_lit) => match expr_lit.lit {
-                Lit::Str(s) => s.value(),
-                _ => panic!("#[task_handler] expects name to be a string literal: name = \"...\""),
-            },
-            _ => panic!("#[task_handler] expects name to be a string literal: name = \"...\""),
-        },
-        _ => panic!("#[task_handler] requires syntax: #[task_handler(name = \"...\")]"),
-    };
+    let args = HandlerArgs::parse(metas);
+
+    let name_value = args.name;
+    let topic_tokens = option_tokens(args.topic.map(|topic| quote! { #topic }));
+    let lock_duration_tokens = option_tokens(args.lock_duration.map(|lock_duration| quote! { #lock_duration }));
+    let variables_tokens = option_tokens(args.variables.map(|variables| quote! { &[#(#variables),*] }));
+    let local_variables = args.local_variables;
+
+    let process_definition_keys = args.process_definition_keys;
+    let process_definition_version_tag_tokens = option_tokens(args.process_definition_version_tag.map(|tag| quote! { #tag }));
+    let business_key_tokens = option_tokens(args.business_key.map(|business_key| quote! { #business_key }));
+    let process_variables = args.process_variables.iter().map(|(name, value)| quote! { (#name, #value) });
 
     let fn_ident = input_fn.sig.ident.clone();
 
-    // Resolve the runtime crate (operaton-task-worker) crate path as used by the depending crate
-    let runtime_crate_ident = match crate_name("operaton-task-worker") {
-        Ok(FoundCrate::Itself) => format_ident!("operaton_task_worker"),
-        Ok(FoundCrate::Name(name)) => format_ident!("{}", name),
-        Err(_) => format_ident!("operaton_task_worker"),
+    let runtime_crate_ident = runtime_crate();
+
+    // Handlers may omit the task context argument, the registry only passes it to handlers which take it
+    let func_tokens = match input_fn.sig.inputs.len() {
+        1 => quote! { #runtime_crate_ident::types::HandlerFn::Plain(#fn_ident) },
+        2 => quote! { #runtime_crate_ident::types::HandlerFn::WithContext(#fn_ident) },
+        _ => panic!("#[task_handler] expects a function with the arguments (&InputVariables) or (&InputVariables, &TaskContext)"),
     };
 
+    let retry_backoff_tokens = option_tokens(args.retry_backoff.map(|backoff| {
+        let variant = match backoff.as_str() {
+            "fixed" => format_ident!("Fixed"),
+            "linear" => format_ident!("Linear"),
+            "exponential" => format_ident!("Exponential"),
+            _ => panic!("#[task_handler] expects retry_backoff to be \"fixed\", \"linear\" or \"exponential\""),
+        };
+        quote! { #runtime_crate_ident::retry::Backoff::#variant }
+    }));
+    let max_retries_tokens = option_tokens(args.max_retries.map(|max_retries| quote! { #max_retries }));
+    let retry_delay_tokens = option_tokens(args.retry_delay.map(|delay| quote! { #delay }));
+    let retry_max_delay_tokens = option_tokens(args.retry_max_delay.map(|max_delay| quote! { #max_delay }));
+
     // Emit original function unchanged + inventory registration in the using crate's context
     let expanded = quote! {
         #input_fn
@@ -56,11 +97,440 @@ pub fn task_handler(attr: TokenStream, item: TokenStream) -> TokenStream {
             #runtime_crate_ident::inventory::submit! {
                 #runtime_crate_ident::registry::Handler {
                     name: #name_value,
-                    func: #fn_ident,
+                    topic: #topic_tokens,
+                    lock_duration: #lock_duration_tokens,
+                    variables: #variables_tokens,
+                    local_variables: #local_variables,
+                    retry_policy: #runtime_crate_ident::retry::RetryPolicyOverride {
+                        backoff: #retry_backoff_tokens,
+                        max_retries: #max_retries_tokens,
+                        delay: #retry_delay_tokens,
+                        max_delay: #retry_max_delay_tokens,
+                    },
+                    filter: #runtime_crate_ident::registry::TaskFilter {
+                        process_definition_keys: &[#(#process_definition_keys),*],
+                        process_definition_version_tag: #process_definition_version_tag_tokens,
+                        business_key: #business_key_tokens,
+                        process_variables: &[#(#process_variables),*],
+                    },
+                    func: #func_tokens,
+                }
+            }
+        };
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// The name of the attribute macro in error messages
+const TASK_HANDLER: &str = "#[task_handler]";
+
+/// The arguments of the `#[task_handler(...)]` attribute
+struct HandlerArgs {
+    name: String,
+    topic: Option<String>,
+    lock_duration: Option<u64>,
+    variables: Option<Vec<String>>,
+    local_variables: bool,
+    max_retries: Option<u32>,
+    retry_backoff: Option<String>,
+    retry_delay: Option<u64>,
+    retry_max_delay: Option<u64>,
+    process_definition_keys: Vec<String>,
+    process_definition_version_tag: Option<String>,
+    business_key: Option<String>,
+    /// The variable names and their values encoded as JSON
+    process_variables: Vec<(String, String)>,
+}
+
+impl HandlerArgs {
+    fn parse(metas: Punctuated<Meta, Token![,]>) -> Self {
+        let mut name: Option<String> = None;
+        let mut args = HandlerArgs {
+            name: String::new(),
+            topic: None,
+            lock_duration: None,
+            variables: None,
+            local_variables: false,
+            max_retries: None,
+            retry_backoff: None,
+            retry_delay: None,
+            retry_max_delay: None,
+            process_definition_keys: Vec::new(),
+            process_definition_version_tag: None,
+            business_key: None,
+            process_variables: Vec::new(),
+        };
+
+        for meta in metas {
+            let nv = match meta {
+                Meta::NameValue(nv) => nv,
+                Meta::List(list) if list.path.is_ident("process_variables") => {
+                    let variables = list
+                        .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+                        .unwrap_or_else(|err| panic!("#[task_handler] expects process_variables(name = value, ...): {err}"));
+                    for variable in variables {
+                        let name = variable
+                            .path
+                            .get_ident()
+                            .map(|ident| ident.to_string())
+                            .expect("#[task_handler] expects process_variables(name = value, ...)");
+                        args.process_variables.push((name, expect_json(&variable.value, "process_variables", TASK_HANDLER)));
+                    }
+                    continue;
+                }
+                _ => panic!("#[task_handler] requires syntax: #[task_handler(name = \"...\")]"),
+            };
+            let key = nv.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+            match key.as_str() {
+                "name" => name = Some(expect_str(&nv.value, "name", TASK_HANDLER)),
+                "topic" => args.topic = Some(expect_str(&nv.value, "topic", TASK_HANDLER)),
+                "lock_duration" => args.lock_duration = Some(expect_int(&nv.value, "lock_duration", TASK_HANDLER)),
+                "variables" => args.variables = Some(expect_str_array(&nv.value, "variables", TASK_HANDLER)),
+                "local_variables" => args.local_variables = expect_bool(&nv.value, "local_variables", TASK_HANDLER),
+                "max_retries" => args.max_retries = Some(expect_int(&nv.value, "max_retries", TASK_HANDLER)),
+                "retry_backoff" => args.retry_backoff = Some(expect_str(&nv.value, "retry_backoff", TASK_HANDLER)),
+                "retry_delay" => args.retry_delay = Some(expect_int(&nv.value, "retry_delay", TASK_HANDLER)),
+                "retry_max_delay" => args.retry_max_delay = Some(expect_int(&nv.value, "retry_max_delay", TASK_HANDLER)),
+                "process_definition_keys" => {
+                    args.process_definition_keys = expect_str_array(&nv.value, "process_definition_keys", TASK_HANDLER)
+                }
+                "process_definition_version_tag" => {
+                    args.process_definition_version_tag = Some(expect_str(&nv.value, "process_definition_version_tag", TASK_HANDLER))
+                }
+                "business_key" => args.business_key = Some(expect_str(&nv.value, "business_key", TASK_HANDLER)),
+                _ => panic!(
+                    "#[task_handler] supports the arguments `name`, `topic`, `lock_duration`, `variables`, `local_variables`, \
+                     `max_retries`, `retry_backoff`, `retry_delay`, `retry_max_delay`, `process_definition_keys`, \
+                     `process_definition_version_tag`, `business_key` and `process_variables(...)`"
+                ),
+            }
+        }
+
+        args.name = name.expect("#[task_handler] requires syntax: #[task_handler(name = \"...\")]");
+        args
+    }
+}
+
+/// Derive macro to build a struct from the input variables of a task, see `operaton_task_worker::variables::FromVariables`.
+///
+/// ```ignore
+/// #[derive(FromVariables)]
+/// struct InvoiceInput {
+///     #[variable(rename = "invoiceId")]
+///     invoice_id: String,
+///     #[variable(default)]
+///     reminders: i32,
+///     approver: Option<String>,
+///     #[variable(json)]
+///     items: Vec<Item>,
+/// }
+/// ```
+#[proc_macro_derive(FromVariables, attributes(variable))]
+pub fn derive_from_variables(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let runtime_crate_ident = runtime_crate();
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = named_fields(&input.data, "FromVariables");
+    // The locals of the generated code must not clash with the locals named after the fields
+    let input_var = macro_local("input");
+    let errors_var = macro_local("errors");
+    let mut reads = Vec::new();
+    let mut inits = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.clone().expect("named field");
+        let args = VariableArgs::parse(&field.attrs, "FromVariables");
+        if args.skip_none || args.transient || args.local {
+            panic!("#[derive(FromVariables)] does not support `skip_none`, `transient` and `local`");
+        }
+        let name = args.rename.unwrap_or_else(|| field_ident.to_string());
+        let optional_inner = option_inner_type(&field.ty);
+
+        let value_type = optional_inner.unwrap_or(&field.ty);
+        let (read_type, unwrap_json) = if args.json {
+            (quote! { #runtime_crate_ident::variables::Json<#value_type> }, quote! { .map(|json| json.0) })
+        } else {
+            (quote! { #value_type }, quote! {})
+        };
+
+        let read = match (optional_inner.is_some(), &args.default) {
+            (true, _) => quote! {
+                #runtime_crate_ident::variables::optional_variable::<#read_type>(#input_var, #name, &mut #errors_var)#unwrap_json
+            },
+            (false, Some(default)) => {
+                let default_value = match default {
+                    Some(function) => {
+                        let function: syn::Path = syn::parse_str(function)
+                            .unwrap_or_else(|err| panic!("#[derive(FromVariables)] expects default to be a function path: {err}"));
+                        quote! { #function() }
+                    }
+                    None => quote! { ::core::default::Default::default() },
+                };
+                quote! {
+                    ::core::option::Option::Some(
+                        #runtime_crate_ident::variables::optional_variable::<#read_type>(#input_var, #name, &mut #errors_var)#unwrap_json
+                            .unwrap_or_else(|| #default_value)
+                    )
+                }
+            }
+            (false, None) => quote! {
+                #runtime_crate_ident::variables::variable::<#read_type>(#input_var, #name, &mut #errors_var)#unwrap_json
+            },
+        };
+        reads.push(quote! { let #field_ident = #read; });
+        inits.push(if optional_inner.is_some() {
+            quote! { #field_ident }
+        } else {
+            quote! { #field_ident: #field_ident.expect("the variable was checked") }
+        });
+    }
+
+    let expanded = quote! {
+        impl #impl_generics #runtime_crate_ident::variables::FromVariables for #ident #type_generics #where_clause {
+            fn from_variables(
+                #input_var: &#runtime_crate_ident::types::InputVariables,
+            ) -> ::core::result::Result<Self, #runtime_crate_ident::variables::VariablesError> {
+                let mut #errors_var = ::std::vec::Vec::new();
+                #(#reads)*
+                if !#errors_var.is_empty() {
+                    return ::core::result::Result::Err(#runtime_crate_ident::variables::VariablesError { errors: #errors_var });
                 }
+                ::core::result::Result::Ok(Self { #(#inits),* })
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Derive macro to convert a struct into the output variables of a task, see `operaton_task_worker::variables::IntoVariables`.
+///
+/// ```ignore
+/// #[derive(IntoVariables)]
+/// struct InvoiceResult {
+///     #[variable(rename = "invoiceNumber")]
+///     invoice_number: String,
+///     #[variable(skip_none)]
+///     discount: Option<f64>,
+///     #[variable(transient, local)]
+///     reviewer: String,
+///     #[variable(json)]
+///     items: Vec<Item>,
+/// }
+/// ```
+#[proc_macro_derive(IntoVariables, attributes(variable))]
+pub fn derive_into_variables(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let runtime_crate_ident = runtime_crate();
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = named_fields(&input.data, "IntoVariables");
+    let variables_var = macro_local("variables");
+    let errors_var = macro_local("errors");
+    let value_var = macro_local("value");
+    let mut inserts = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.clone().expect("named field");
+        let args = VariableArgs::parse(&field.attrs, "IntoVariables");
+        if args.default.is_some() {
+            panic!("#[derive(IntoVariables)] does not support `default`");
+        }
+        let name = args.rename.unwrap_or_else(|| field_ident.to_string());
+        let optional = option_inner_type(&field.ty).is_some();
+        if args.skip_none && !optional {
+            panic!("#[derive(IntoVariables)] supports `skip_none` only for `Option` fields");
+        }
+        let (transient, local) = (args.transient, args.local);
+
+        let insert = |value: proc_macro2::TokenStream| {
+            quote! {
+                #runtime_crate_ident::variables::insert_variable(&mut #variables_var, &mut #errors_var, #name, #value, #transient, #local);
             }
         };
+        inserts.push(match (args.skip_none, args.json) {
+            (true, json) => {
+                let value = if json { quote! { #runtime_crate_ident::variables::Json(#value_var) } } else { quote! { #value_var } };
+                let insert = insert(value);
+                quote! { if let ::core::option::Option::Some(#value_var) = self.#field_ident { #insert } }
+            }
+            (false, true) if optional => insert(quote! { self.#field_ident.map(#runtime_crate_ident::variables::Json) }),
+            (false, true) => insert(quote! { #runtime_crate_ident::variables::Json(self.#field_ident) }),
+            (false, false) => insert(quote! { self.#field_ident }),
+        });
+    }
+
+    let expanded = quote! {
+        impl #impl_generics #runtime_crate_ident::variables::IntoVariables for #ident #type_generics #where_clause {
+            fn into_variables(
+                self,
+            ) -> ::core::result::Result<#runtime_crate_ident::types::OutputVariables, #runtime_crate_ident::variables::VariablesError> {
+                let mut #variables_var = #runtime_crate_ident::types::OutputVariables::new();
+                let mut #errors_var = ::std::vec::Vec::new();
+                #(#inserts)*
+                if !#errors_var.is_empty() {
+                    return ::core::result::Result::Err(#runtime_crate_ident::variables::VariablesError { errors: #errors_var });
+                }
+                ::core::result::Result::Ok(#variables_var)
+            }
+        }
     };
 
     TokenStream::from(expanded)
 }
+
+/// The arguments of the `#[variable(...)]` field attribute
+struct VariableArgs {
+    rename: Option<String>,
+    /// `Some(None)` for `default`, `Some(Some(path))` for `default = "path"`
+    default: Option<Option<String>>,
+    json: bool,
+    skip_none: bool,
+    transient: bool,
+    local: bool,
+}
+
+impl VariableArgs {
+    fn parse(attrs: &[syn::Attribute], derive: &str) -> Self {
+        let macro_name = format!("#[derive({derive})]");
+        let mut args = VariableArgs { rename: None, default: None, json: false, skip_none: false, transient: false, local: false };
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("variable")) {
+            let metas = attr
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .unwrap_or_else(|err| panic!("#[derive({derive})] expects #[variable(...)]: {err}"));
+            for meta in metas {
+                match &meta {
+                    Meta::NameValue(nv) if nv.path.is_ident("rename") => args.rename = Some(expect_str(&nv.value, "rename", &macro_name)),
+                    Meta::NameValue(nv) if nv.path.is_ident("default") => {
+                        args.default = Some(Some(expect_str(&nv.value, "default", &macro_name)))
+                    }
+                    Meta::Path(path) if path.is_ident("default") => args.default = Some(None),
+                    Meta::Path(path) if path.is_ident("json") => args.json = true,
+                    Meta::Path(path) if path.is_ident("skip_none") => args.skip_none = true,
+                    Meta::Path(path) if path.is_ident("transient") => args.transient = true,
+                    Meta::Path(path) if path.is_ident("local") => args.local = true,
+                    _ => panic!(
+                        "#[derive({derive})] supports the field arguments `rename`, `default`, `json`, `skip_none`, \
+                         `transient` and `local`"
+                    ),
+                }
+            }
+        }
+        args
+    }
+}
+
+/// The named fields of a struct
+fn named_fields<'a>(data: &'a Data, derive: &str) -> &'a Punctuated<syn::Field, Token![,]> {
+    match data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => panic!("#[derive({derive})] only supports structs with named fields"),
+        },
+        _ => panic!("#[derive({derive})] only supports structs"),
+    }
+}
+
+/// The type `T` of a field of type `Option<T>`
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else { return None };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match args.args.first() {
+            Some(GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A local variable of generated code, which is hygienic and cannot clash with identifiers of the user, e.g. field names
+fn macro_local(name: &str) -> proc_macro2::Ident {
+    proc_macro2::Ident::new(name, proc_macro2::Span::mixed_site())
+}
+
+/// Resolve the runtime crate (operaton-task-worker) crate path as used by the depending crate
+fn runtime_crate() -> proc_macro2::Ident {
+    match crate_name("operaton-task-worker") {
+        Ok(FoundCrate::Itself) => format_ident!("operaton_task_worker"),
+        Ok(FoundCrate::Name(name)) => format_ident!("{}", name),
+        Err(_) => format_ident!("operaton_task_worker"),
+    }
+}
+
+fn option_tokens(value: Option<proc_macro2::TokenStream>) -> proc_macro2::TokenStream {
+    match value {
+        Some(value) => quote! { ::core::option::Option::Some(#value) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
+
+// The `expect_*` helpers take the macro which reports the error, e.g. `#[task_handler]` or `#[derive(FromVariables)]`
+
+fn expect_lit<'a>(expr: &'a Expr, key: &str, macro_name: &str) -> &'a Lit {
+    match expr {
+        Expr::Lit(expr_lit) => &expr_lit.lit,
+        _ => panic!("{macro_name} expects {key} to be a literal"),
+    }
+}
+
+fn expect_str(expr: &Expr, key: &str, macro_name: &str) -> String {
+    match expect_lit(expr, key, macro_name) {
+        Lit::Str(s) => s.value(),
+        _ => panic!("{macro_name} expects {key} to be a string literal: {key} = \"...\""),
+    }
+}
+
+fn expect_int<T: std::str::FromStr>(expr: &Expr, key: &str, macro_name: &str) -> T
+where
+    T::Err: std::fmt::Display,
+{
+    match expect_lit(expr, key, macro_name) {
+        Lit::Int(i) => i.base10_parse().unwrap_or_else(|err| panic!("{macro_name} expects {key} to be an integer: {err}")),
+        _ => panic!("{macro_name} expects {key} to be an integer"),
+    }
+}
+
+fn expect_bool(expr: &Expr, key: &str, macro_name: &str) -> bool {
+    match expect_lit(expr, key, macro_name) {
+        Lit::Bool(b) => b.value,
+        _ => panic!("{macro_name} expects {key} to be `true` or `false`"),
+    }
+}
+
+/// A string, integer, float or boolean literal encoded as JSON
+fn expect_json(expr: &Expr, key: &str, macro_name: &str) -> String {
+    match expect_lit(expr, key, macro_name) {
+        Lit::Str(s) => json_string(&s.value()),
+        Lit::Int(i) => i.base10_digits().to_string(),
+        Lit::Float(f) => f.base10_digits().to_string(),
+        Lit::Bool(b) => b.value.to_string(),
+        _ => panic!("{macro_name} expects {key} values to be string, number or boolean literals"),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn expect_str_array(expr: &Expr, key: &str, macro_name: &str) -> Vec<String> {
+    match expr {
+        Expr::Array(array) => array.elems.iter().map(|elem| expect_str(elem, key, macro_name)).collect(),
+        _ => panic!("{macro_name} expects {key} to be an array of string literals: {key} = [\"...\"]"),
+    }
+}
@@ -1,185 +1,208 @@
+//! This module includes the client for the Operaton REST API
+
 use std::collections::HashMap;
-use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
 
-use log::{error, info, trace};
+use log::{error, info, trace, warn};
 use url::Url;
 
+use crate::auth::{auth_provider_from_config, AuthProvider};
+use crate::deployment::{Deployment, DeploymentResult};
+use crate::engine::{MessageCorrelation, ProcessInstance, ProcessStart, Signal};
+use crate::error::{EngineError, OperatonError};
+use crate::retry::RetryPolicy;
 use crate::settings::ConfigParams;
-use crate::structures::process_variables::{parse_process_instance_variables, ProcessInstanceVariable};
+use crate::tls::configure_tls;
 use crate::types::OutputVariables;
+use crate::query::ExternalTaskQuery;
+use crate::structures::engine_version::EngineVersion;
+use crate::structures::external_task::ExternalTask;
 use crate::structures::service_task::ServiceTask;
 
-pub async fn get_open_service_tasks(config: &ConfigParams) -> Result<Vec<ServiceTask>, Box<dyn Error>> {
-    let mut service_tasks_endpoint = config.url().clone();
-    service_tasks_endpoint.set_path("engine-rest/external-task");
-    info!("Fetch data at {}", service_tasks_endpoint);
-
-    // Build the request with optional Basic Auth when username is provided
-    let client = reqwest::Client::new();
-    let mut request = client.get(service_tasks_endpoint.clone());
-
-    if !config.username().is_empty() {
-        request = request.basic_auth(config.username().to_string(), Some(config.password().to_string()));
-        trace!("Using HTTP Basic authentication");
-    } else {
-        trace!("No HTTP authentication configured (empty username)");
-    }
-
-    match request.send().await {
-        Ok(response) => {
-            match response.json().await {
-                Ok(unwrapped_json) => {
-                    let service_tasks: Vec<ServiceTask> = unwrapped_json;
-                    trace!("Parsed: {:#?}", service_tasks);
-                    Ok(service_tasks)
-                },
-                Err(err) => {
-                    error!("An error occurred while parsing the JSON: {:#?}", err);
-                    Err(err.into())
-                }
-            }
-        },
-        Err(err) => {
-            error!(
-                "Error while calling API endpoint '{}': {:#?}",
-                service_tasks_endpoint,
-                err
-            );
-            Err(err.into())
-        }
-    }
-}
+/// Additional time in milliseconds the HTTP client waits beyond the long polling timeout,
+/// so that the engine can answer before the client cuts the request off.
+const LONG_POLLING_TIMEOUT_MARGIN_MS: u64 = 10_000;
 
-pub fn build_authenticated_request(
-    client: &reqwest::Client,
-    url: Url,
-    username: &str,
-    password: &str,
-) -> reqwest::RequestBuilder {
-    let mut request = client.get(url);
+/// The longest `Retry-After` in milliseconds the client honors without a maximum retry delay, so a single response
+/// cannot stall the worker for hours
+const MAX_RETRY_AFTER_MS: u64 = 60_000;
 
-    if !username.is_empty() {
-        request = request.basic_auth(username, Some(password));
-        trace!("Using HTTP Basic authentication");
-    } else {
-        trace!("No HTTP authentication configured (empty username)");
-    }
+/// A client for the Operaton REST API.
+///
+/// The client owns one configured [reqwest::Client], so all calls share a connection pool.
+/// Cloning the client is cheap and the clones share the same connection pool, which allows the
+/// polling loop and user code to use the same client.
+///
+/// ```rust
+/// use operaton_task_worker::OperatonClient;
+/// use operaton_task_worker::settings::ConfigParams;
+///
+/// let client = OperatonClient::new(&ConfigParams::default()).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OperatonClient {
+    http: reqwest::Client,
+    base_url: Url,
+    rest_root: String,
+    engine_name: Option<String>,
+    auth: Arc<dyn AuthProvider>,
+    worker_id: String,
+    http_retry: RetryPolicy,
+}
 
-    request
+/// A topic subscription which is sent with a fetchAndLock request
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicSubscription {
+    pub topic_name: String,
+    pub lock_duration: u64,
+    /// The variables to fetch with the task, `None` fetches all variables visible to the task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<Vec<String>>,
+    /// Whether only the local variables of the task's execution are fetched
+    pub local_variables: bool,
+    /// Only fetch tasks of these tenants, `None` fetches tasks of all tenants
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id_in: Option<Vec<String>>,
+    /// Only fetch tasks which do not belong to a tenant
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub without_tenant_id: bool,
+    /// Only fetch tasks of these process definitions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_definition_key_in: Option<Vec<String>>,
+    /// Only fetch tasks of process definitions with this version tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_definition_version_tag: Option<String>,
+    /// Only fetch tasks of process instances with this business key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_key: Option<String>,
+    /// Only fetch tasks of process instances with these variable values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_variables: Option<HashMap<String, serde_json::Value>>,
+    /// Whether Json and Object variables are sent deserialized, `false` sends their serialized documents
+    pub deserialize_values: bool,
 }
 
-pub fn build_authenticated_post(
-    client: &reqwest::Client,
-    url: Url,
-    username: &str,
-    password: &str,
-) -> reqwest::RequestBuilder {
-    let mut request = client.post(url);
+/// The parameters of a fetchAndLock request
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchAndLock {
+    /// The maximum number of tasks to fetch and lock
+    pub max_tasks: usize,
+    /// Whether tasks with a higher priority are fetched first
+    pub use_priority: bool,
+    /// The long polling timeout in milliseconds, `None` disables long polling
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub async_response_timeout: Option<u64>,
+    pub topics: Vec<TopicSubscription>,
+}
 
-    if !username.is_empty() {
-        request = request.basic_auth(username, Some(password));
-        trace!("Using HTTP Basic authentication");
-    } else {
-        trace!("No HTTP authentication configured (empty username)");
+impl FetchAndLock {
+    /// Build the fetchAndLock parameters from the configuration
+    pub fn from_config(config: &ConfigParams, topics: Vec<TopicSubscription>) -> Self {
+        Self {
+            max_tasks: config.max_tasks(),
+            use_priority: config.use_priority(),
+            async_response_timeout: config.long_polling().then(|| config.async_response_timeout()),
+            topics,
+        }
     }
+}
 
-    request
+#[derive(serde::Deserialize)]
+struct CountResult {
+    count: u64,
 }
 
-#[derive(serde::Serialize)]
+/// A variable instance as returned by the variable instance query, reduced to what is needed to find its data
+#[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct LockRequest<'a> {
-    worker_id: &'a str,
-    lock_duration: u64,
+struct VariableInstanceRef {
+    id: String,
+    /// The scope the variable is defined in
+    activity_instance_id: Option<String>,
+    /// The serialized value, as the query is sent with `deserializeValues=false`
+    #[serde(default)]
+    value: serde_json::Value,
 }
 
-pub async fn lock_external_task(
-    config: &ConfigParams,
-    external_task_id: &str,
-    lock_duration_ms: u64,
-) -> Result<(), Box<dyn Error>> {
-    let mut endpoint = config.url().clone();
-    let path_string = format!(
-        "engine-rest/external-task/{}/lock",
-        external_task_id
-    );
-    endpoint.set_path(path_string.as_str());
-    info!("Lock external task at {}", endpoint);
-
-    let client = reqwest::Client::new();
-    let request = build_authenticated_post(
-        &client,
-        endpoint.clone(),
-        config.username(),
-        config.password(),
-    )
-    .json(&LockRequest { worker_id: config.id(), lock_duration: lock_duration_ms });
-
-    let response = request.send().await.map_err(|err| {
-        error!(
-            "Error while calling API endpoint '{}': {:#?}",
-            endpoint, err
-        );
-        err
-    })?;
+/// An activity instance of the activity instance tree of a process instance, reduced to the tree of scopes
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityInstance {
+    id: String,
+    #[serde(default)]
+    child_activity_instances: Vec<ActivityInstance>,
+}
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_else(|_| "<no body>".to_string());
-        error!("Lock request failed: status={} body={} ", status, body);
-        return Err(format!("Lock failed with status {status}").into());
+impl ActivityInstance {
+    /// The ids of the activity instance with the given id and of its enclosing activity instances, innermost first
+    fn scope_chain(&self, id: &str) -> Option<Vec<String>> {
+        if self.id == id {
+            return Some(vec![self.id.clone()]);
+        }
+        let mut chain = self.child_activity_instances.iter().find_map(|child| child.scope_chain(id))?;
+        chain.push(self.id.clone());
+        Some(chain)
     }
-
-    trace!("Task '{}' locked for {} ms", external_task_id, lock_duration_ms);
-    Ok(())
 }
 
-pub async fn get_process_instance_variables(
-    config: &ConfigParams,
-    process_instance_id: &str,
-) -> Result<HashMap<String, ProcessInstanceVariable>, Box<dyn Error>> {
-    let mut endpoint = config.url().clone();
-    let path_string = "engine-rest/variable-instance";
-
-    endpoint.set_path(path_string);
-    endpoint.set_query(Some(format!("processInstanceIdIn={}", process_instance_id).as_str()));
-
-    info!("Fetch external task variables at {}", endpoint);
-
-    let client = reqwest::Client::new();
-    let request = build_authenticated_request(
-        &client,
-        endpoint.clone(),
-        config.username(),
-        config.password(),
-    );
-
-    let response = request.send().await.map_err(|err| {
-        error!(
-            "Error while calling API endpoint '{}': {:#?}",
-            endpoint, err
-        );
-        err
-    })?;
+/// A failed request with the delay the engine asks for before retrying it
+struct FailedAttempt {
+    error: OperatonError,
+    retry_after: Option<Duration>,
+}
 
-    let body = response.text().await.map_err(|err| {
-        error!("An error occurred while reading the response body: {:#?}", err);
-        err
-    })?;
+/// Parse a `Retry-After` header, given in seconds or as HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => httpdate::parse_http_date(value.trim())
+            .ok()
+            .map(|date| date.duration_since(std::time::SystemTime::now()).unwrap_or_default()),
+    }
+}
 
-    trace!("Variables raw: {}", body);
+/// Limit the `Retry-After` of the engine by the maximum retry delay, or by [MAX_RETRY_AFTER_MS] without one
+fn limit_retry_after(retry_after: Duration, max_delay: u64) -> Duration {
+    let limit = if max_delay > 0 { max_delay } else { MAX_RETRY_AFTER_MS };
+    retry_after.min(Duration::from_millis(limit))
+}
 
-    let parsed = parse_process_instance_variables(&body);
-    trace!("Parsed variables: {:#?}", parsed);
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FetchAndLockRequest<'a> {
+    worker_id: &'a str,
+    #[serde(flatten)]
+    fetch: &'a FetchAndLock,
+}
 
-    Ok(parsed)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtendLockRequest<'a> {
+    worker_id: &'a str,
+    new_duration: u64,
 }
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 struct CompleteRequest<'a> {
     worker_id: &'a str,
-    variables: crate::types::OutputVariables,
+    variables: OutputVariables,
+    #[serde(skip_serializing_if = "OutputVariables::is_empty")]
+    local_variables: OutputVariables,
+}
+
+/// Split the variables into the process variables and the local variables of the task's execution
+fn split_local_variables(variables: OutputVariables) -> (OutputVariables, OutputVariables) {
+    let (local_variables, variables): (OutputVariables, OutputVariables) =
+        variables.into_iter().partition(|(_, variable)| variable.local);
+    let local_variables = local_variables
+        .into_iter()
+        .map(|(name, variable)| (name, variable.with_local(false)))
+        .collect();
+    (variables, local_variables)
 }
 
 #[derive(serde::Serialize)]
@@ -201,144 +224,957 @@ struct BpmnErrorRequest<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     error_message: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    variables: Option<crate::types::OutputVariables>,
+    variables: Option<OutputVariables>,
 }
 
-pub async fn complete_external_task(
-    config: &ConfigParams,
-    external_task_id: &str,
-    variables: crate::types::OutputVariables,
-) -> Result<(), Box<dyn Error>> {
-    let mut endpoint = config.url().clone();
-    let path_string = format!(
-        "engine-rest/external-task/{}/complete",
-        external_task_id
-    );
-    endpoint.set_path(path_string.as_str());
-    info!("Complete external task at {}", endpoint);
-
-    let client = reqwest::Client::new();
-    let request = build_authenticated_post(
-        &client,
-        endpoint.clone(),
-        config.username(),
-        config.password(),
-    )
-    .json(&CompleteRequest { worker_id: config.id(), variables });
-
-    let response = request.send().await.map_err(|err| {
-        error!(
-            "Error while calling API endpoint '{}': {:#?}",
-            endpoint, err
-        );
-        err
-    })?;
+impl OperatonClient {
+    /// Create a new client with an HTTP client configured from the timeout, connection pool and TLS settings
+    pub fn new(config: &ConfigParams) -> Result<Self, OperatonError> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(Duration::from_millis(config.connect_timeout()))
+            .pool_idle_timeout(Duration::from_millis(config.pool_idle_timeout()))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host());
+
+        if config.request_timeout() > 0 {
+            builder = builder.timeout(Duration::from_millis(config.request_timeout()));
+        }
+        if config.tcp_keepalive() > 0 {
+            builder = builder.tcp_keepalive(Duration::from_millis(config.tcp_keepalive()));
+        }
+        let builder = configure_tls(builder, config)?;
+
+        Ok(Self::with_http_client(config, builder.build()?))
+    }
+
+    /// Create a new client which uses an already configured HTTP client
+    pub fn with_http_client(config: &ConfigParams, http: reqwest::Client) -> Self {
+        Self {
+            auth: auth_provider_from_config(config, &http),
+            http,
+            base_url: config.url().clone(),
+            rest_root: config.rest_root().to_string(),
+            engine_name: config.engine_name().map(str::to_string),
+            worker_id: config.id().to_string(),
+            http_retry: config.http_retry_policy(),
+        }
+    }
+
+    /// Replace the authentication provider built from the configuration, e.g. with a custom [AuthProvider]
+    pub fn with_auth_provider(mut self, auth: Arc<dyn AuthProvider>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Replace the retry policy for requests which failed with a transient error
+    pub fn with_http_retry_policy(mut self, http_retry: RetryPolicy) -> Self {
+        self.http_retry = http_retry;
+        self
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_else(|_| "<no body>".to_string());
-        error!("Complete request failed: status={} body={} ", status, body);
-        return Err(format!("Complete failed with status {status}").into());
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
     }
 
-    trace!("Task '{}' completed", external_task_id);
-    Ok(())
+    /// The URL of an engine-scoped resource: `{base url}/{rest root}[/engine/{engine name}]/{path}`.
+    /// The path of the base URL is preserved.
+    fn endpoint(&self, path: &str) -> Url {
+        self.build_endpoint(path, true)
+    }
+
+    /// The URL of a resource which does not belong to a process engine: `{base url}/{rest root}/{path}`
+    fn root_endpoint(&self, path: &str) -> Url {
+        self.build_endpoint(path, false)
+    }
+
+    fn build_endpoint(&self, path: &str, engine_scoped: bool) -> Url {
+        let mut endpoint = self.base_url.clone();
+        endpoint.set_query(None);
+        // URLs which cannot be a base (e.g. `mailto:`) have no path segments and are rejected by reqwest anyway
+        if let Ok(mut segments) = endpoint.path_segments_mut() {
+            segments.pop_if_empty();
+            segments.extend(self.rest_root.split('/').filter(|segment| !segment.is_empty()));
+            if let Some(engine_name) = self.engine_name.as_ref().filter(|_| engine_scoped) {
+                segments.push("engine").push(engine_name);
+            }
+            segments.extend(path.split('/'));
+        }
+        endpoint
+    }
+
+    /// A GET request, which is authenticated when it is sent
+    fn get(&self, url: Url) -> reqwest::RequestBuilder {
+        self.http.get(url)
+    }
+
+    /// A POST request, which is authenticated when it is sent
+    fn post(&self, url: Url) -> reqwest::RequestBuilder {
+        self.http.post(url)
+    }
+
+    /// Send a request and turn a non-successful status into an [OperatonError::Engine]
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        endpoint: &Url,
+        operation: &str,
+    ) -> Result<reqwest::Response, OperatonError> {
+        self.attempt_authenticated(request, endpoint, operation).await.map_err(|failed| failed.error)
+    }
+
+    /// Authenticate and send a request once.
+    ///
+    /// The engine may reject credentials before they expire, e.g. a revoked access token. So on `401 Unauthorized`,
+    /// the cached credentials are invalidated and, if the provider can refresh them, the request is repeated once with
+    /// fresh credentials. Requests with a streaming body cannot be repeated, but the next request uses fresh credentials.
+    async fn attempt_authenticated(
+        &self,
+        request: reqwest::RequestBuilder,
+        endpoint: &Url,
+        operation: &str,
+    ) -> Result<reqwest::Response, FailedAttempt> {
+        let repeated = request.try_clone();
+        let failed = match Self::attempt(self.authenticate(request).await?, endpoint, operation).await {
+            Err(failed) if failed.error.status() == Some(reqwest::StatusCode::UNAUTHORIZED) => failed,
+            result => return result,
+        };
+
+        let refreshed = self.auth.invalidate().await;
+        let Some(repeated) = repeated.filter(|_| refreshed) else {
+            return Err(failed);
+        };
+        warn!("{} request was rejected as unauthorized, repeat it with fresh credentials", operation);
+        Self::attempt(self.authenticate(repeated).await?, endpoint, operation).await
+    }
+
+    async fn authenticate(&self, request: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder, FailedAttempt> {
+        self.auth
+            .authenticate(request)
+            .await
+            .map_err(|error| FailedAttempt { error, retry_after: None })
+    }
+
+    /// Send a request which is safe to repeat, and retry it with backoff while it fails with a transient error
+    async fn send_with_retries(
+        &self,
+        request: reqwest::RequestBuilder,
+        endpoint: &Url,
+        operation: &str,
+    ) -> Result<reqwest::Response, OperatonError> {
+        let mut retry = 0;
+        loop {
+            // Requests with a streaming body cannot be cloned and are sent only once
+            let Some(attempt) = request.try_clone() else {
+                return self.send(request, endpoint, operation).await;
+            };
+
+            let failed = match self.attempt_authenticated(attempt, endpoint, operation).await {
+                Ok(response) => return Ok(response),
+                Err(failed) => failed,
+            };
+            if retry >= self.http_retry.max_retries || !failed.error.is_transient() {
+                return Err(failed.error);
+            }
+
+            retry += 1;
+            let delay = match failed.retry_after {
+                Some(retry_after) => limit_retry_after(retry_after, self.http_retry.max_delay),
+                None => Duration::from_millis(self.http_retry.jittered_delay_for_retry(retry)),
+            };
+            warn!(
+                "{} request failed: {}. Retry {}/{} in {} ms",
+                operation,
+                failed.error,
+                retry,
+                self.http_retry.max_retries,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Send a request once and keep the `Retry-After` of a failed response
+    async fn attempt(
+        request: reqwest::RequestBuilder,
+        endpoint: &Url,
+        operation: &str,
+    ) -> Result<reqwest::Response, FailedAttempt> {
+        let response = request.send().await.map_err(|err| {
+            error!(
+                "Error while calling API endpoint '{}': {:#?}",
+                endpoint, err
+            );
+            FailedAttempt { error: OperatonError::from(err), retry_after: None }
+        })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            let body = response.text().await.unwrap_or_else(|_| "<no body>".to_string());
+            error!("{} request failed: status={} body={} ", operation, status, body);
+            return Err(FailedAttempt {
+                error: OperatonError::Engine { status, error: EngineError::from_body(&body) },
+                retry_after,
+            });
+        }
+
+        Ok(response)
+    }
+
+    /// Read the body of a successful response and deserialize it
+    async fn read_json<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T, OperatonError> {
+        let body = response.text().await.map_err(|err| {
+            error!("An error occurred while reading the response body: {:#?}", err);
+            OperatonError::from(err)
+        })?;
+
+        serde_json::from_str(&body).map_err(|err| {
+            error!("An error occurred while parsing the JSON: {:#?}", err);
+            OperatonError::from(err)
+        })
+    }
+
+    /// Query the version of the engine, which also verifies that the engine is reachable and accepts the credentials
+    pub async fn engine_version(&self) -> Result<EngineVersion, OperatonError> {
+        let endpoint = self.root_endpoint("version");
+        info!("Query the engine version at {}", endpoint);
+
+        let response = self.send_with_retries(self.get(endpoint.clone()), &endpoint, "Version").await?;
+
+        let version: EngineVersion = Self::read_json(response).await?;
+        trace!("Engine version: {}", version);
+        Ok(version)
+    }
+
+    /// Query external tasks, e.g. for monitoring or administration
+    pub async fn query_external_tasks(&self, query: &ExternalTaskQuery) -> Result<Vec<ExternalTask>, OperatonError> {
+        let endpoint = self.endpoint("external-task");
+        info!("Query external tasks at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).query(&query.paging()).json(query);
+        let response = self.send_with_retries(request, &endpoint, "External task query").await?;
+
+        let external_tasks: Vec<ExternalTask> = Self::read_json(response).await?;
+        trace!("Parsed: {:#?}", external_tasks);
+        Ok(external_tasks)
+    }
+
+    /// Count the external tasks matching a query; the paging and sorting of the query are ignored
+    pub async fn count_external_tasks(&self, query: &ExternalTaskQuery) -> Result<u64, OperatonError> {
+        let endpoint = self.endpoint("external-task/count");
+        info!("Count external tasks at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).json(&query.without_sorting());
+        let response = self.send_with_retries(request, &endpoint, "External task count").await?;
+
+        let count: CountResult = Self::read_json(response).await?;
+        Ok(count.count)
+    }
+
+    /// Correlate a message with a waiting process instance or a message start event
+    pub async fn correlate_message(&self, message: &MessageCorrelation) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint("message");
+        info!("Correlate message at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).json(message);
+        self.send(request, &endpoint, "Message correlation").await?;
+        Ok(())
+    }
+
+    /// Send a signal to all process instances waiting for it
+    pub async fn send_signal(&self, signal: &Signal) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint("signal");
+        info!("Send signal at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).json(signal);
+        self.send(request, &endpoint, "Signal").await?;
+        Ok(())
+    }
+
+    /// Start a process instance of the latest version of a process definition
+    pub async fn start_process_instance(&self, start: &ProcessStart) -> Result<ProcessInstance, OperatonError> {
+        let endpoint = self.endpoint(&start.path());
+        info!("Start process instance at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).json(start);
+        let response = self.send(request, &endpoint, "Process start").await?;
+
+        let process_instance: ProcessInstance = Self::read_json(response).await?;
+        trace!("Started process instance {}", process_instance.id);
+        Ok(process_instance)
+    }
+
+    /// Download the content of a File or Bytes variable instance
+    pub async fn variable_instance_data(&self, variable_instance_id: &str) -> Result<Vec<u8>, OperatonError> {
+        let endpoint = self.endpoint(&format!("variable-instance/{}/data", variable_instance_id));
+        info!("Download variable data at {}", endpoint);
+
+        let request = self.get(endpoint.clone());
+        let response = self.send_with_retries(request, &endpoint, "Variable data").await?;
+
+        let data = response.bytes().await.map_err(|err| {
+            error!("An error occurred while reading the variable data: {:#?}", err);
+            OperatonError::from(err)
+        })?;
+        Ok(data.to_vec())
+    }
+
+    /// Find the id of the variable instance with the given name which is visible to an activity instance of a process
+    /// instance, i.e. which is defined in the activity instance or one of its enclosing scopes. A variable of an inner
+    /// scope takes precedence over a variable of the same name in an outer scope. Without an activity instance, only
+    /// the variables of the process instance itself are visible.
+    pub async fn find_variable_instance_id(
+        &self,
+        process_instance_id: &str,
+        activity_instance_id: Option<&str>,
+        name: &str,
+    ) -> Result<Option<String>, OperatonError> {
+        let instance = self.find_variable_instance(process_instance_id, activity_instance_id, name).await?;
+        Ok(instance.map(|instance| instance.id))
+    }
+
+    /// Fetch the serialized value of the variable with the given name which is visible to an activity instance of a
+    /// process instance, e.g. the JSON document of a Json variable as string. The same scoping as in
+    /// [OperatonClient::find_variable_instance_id] applies.
+    pub async fn serialized_variable_value(
+        &self,
+        process_instance_id: &str,
+        activity_instance_id: Option<&str>,
+        name: &str,
+    ) -> Result<Option<serde_json::Value>, OperatonError> {
+        let instance = self.find_variable_instance(process_instance_id, activity_instance_id, name).await?;
+        Ok(instance.map(|instance| instance.value))
+    }
+
+    async fn find_variable_instance(
+        &self,
+        process_instance_id: &str,
+        activity_instance_id: Option<&str>,
+        name: &str,
+    ) -> Result<Option<VariableInstanceRef>, OperatonError> {
+        let scopes = match activity_instance_id {
+            Some(activity_instance_id) => self.activity_instance_scopes(process_instance_id, activity_instance_id).await?,
+            None => vec![process_instance_id.to_string()],
+        };
+
+        let endpoint = self.endpoint("variable-instance");
+        info!("Query variable instance {} at {}", name, endpoint);
+
+        let request = self.get(endpoint.clone()).query(&[
+            ("processInstanceIdIn", process_instance_id),
+            ("activityInstanceIdIn", &scopes.join(",")),
+            ("variableName", name),
+            ("deserializeValues", "false"),
+        ]);
+        let response = self.send_with_retries(request, &endpoint, "Variable instance query").await?;
+
+        let instances: Vec<VariableInstanceRef> = Self::read_json(response).await?;
+        Ok(instances
+            .into_iter()
+            .filter_map(|instance| {
+                let depth = scopes.iter().position(|scope| instance.activity_instance_id.as_deref() == Some(scope))?;
+                Some((depth, instance))
+            })
+            .min_by_key(|(depth, _)| *depth)
+            .map(|(_, instance)| instance))
+    }
+
+    /// The ids of an activity instance and of its enclosing scopes up to the process instance, innermost first
+    async fn activity_instance_scopes(
+        &self,
+        process_instance_id: &str,
+        activity_instance_id: &str,
+    ) -> Result<Vec<String>, OperatonError> {
+        let endpoint = self.endpoint(&format!("process-instance/{}/activity-instances", process_instance_id));
+        info!("Fetch the activity instances at {}", endpoint);
+
+        let request = self.get(endpoint.clone());
+        let response = self.send_with_retries(request, &endpoint, "Activity instance tree").await?;
+
+        let tree: ActivityInstance = Self::read_json(response).await?;
+        // Without the activity instance in the tree, its own variables and those of the process instance are visible
+        Ok(tree.scope_chain(activity_instance_id).unwrap_or_else(|| vec![activity_instance_id.to_string(), tree.id]))
+    }
+
+    /// Deploy resources like BPMN, DMN and form files.
+    /// Multipart bodies cannot be replayed, so the deployment is not retried.
+    pub async fn create_deployment(&self, deployment: &Deployment) -> Result<DeploymentResult, OperatonError> {
+        let endpoint = self.endpoint("deployment/create");
+        info!("Create deployment {} at {}", deployment.name(), endpoint);
+
+        let request = self.post(endpoint.clone()).multipart(deployment.form());
+        let response = self.send(request, &endpoint, "Deployment").await?;
+
+        let result: DeploymentResult = Self::read_json(response).await?;
+        trace!("Created deployment {}", result.id);
+        Ok(result)
+    }
+
+    /// Fetch and lock external tasks for the given topics in one call
+    pub async fn fetch_and_lock(&self, fetch: &FetchAndLock) -> Result<Vec<ServiceTask>, OperatonError> {
+        let endpoint = self.endpoint("external-task/fetchAndLock");
+        info!("Fetch and lock external tasks at {}", endpoint);
+
+        let mut request = self.post(endpoint.clone())
+            .json(&FetchAndLockRequest { worker_id: &self.worker_id, fetch });
+
+        if let Some(async_response_timeout) = fetch.async_response_timeout {
+            request = request.timeout(Duration::from_millis(async_response_timeout + LONG_POLLING_TIMEOUT_MARGIN_MS));
+        }
+
+        let response = self.send_with_retries(request, &endpoint, "Fetch and lock").await?;
+
+        let service_tasks: Vec<ServiceTask> = Self::read_json(response).await?;
+        trace!("Parsed: {:#?}", service_tasks);
+        Ok(service_tasks)
+    }
+
+    /// Extend the lock of an external task locked by this worker to `new_duration_ms` from now
+    pub async fn extend_lock(
+        &self,
+        external_task_id: &str,
+        new_duration_ms: u64,
+    ) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint(&format!("external-task/{}/extendLock", external_task_id));
+        info!("Extend lock of external task at {}", endpoint);
+
+        let request = self.post(endpoint.clone())
+            .json(&ExtendLockRequest { worker_id: &self.worker_id, new_duration: new_duration_ms });
+        self.send_with_retries(request, &endpoint, "Extend lock").await?;
+
+        trace!("Lock of task '{}' extended by {} ms", external_task_id, new_duration_ms);
+        Ok(())
+    }
+
+    /// Unlock an external task, so it can be fetched by other workers right away
+    pub async fn unlock_external_task(&self, external_task_id: &str) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint(&format!("external-task/{}/unlock", external_task_id));
+        info!("Unlock external task at {}", endpoint);
+
+        self.send_with_retries(self.post(endpoint.clone()), &endpoint, "Unlock").await?;
+
+        trace!("Task '{}' unlocked", external_task_id);
+        Ok(())
+    }
+
+    pub async fn complete_external_task(
+        &self,
+        external_task_id: &str,
+        variables: OutputVariables,
+    ) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint(&format!("external-task/{}/complete", external_task_id));
+        info!("Complete external task at {}", endpoint);
+
+        let (variables, local_variables) = split_local_variables(variables);
+
+        let request = self.post(endpoint.clone())
+            .json(&CompleteRequest { worker_id: &self.worker_id, variables, local_variables });
+        // A repeated completion of a task which was completed already fails with "not found", which is not retried
+        self.send_with_retries(request, &endpoint, "Complete").await?;
+
+        trace!("Task '{}' completed", external_task_id);
+        Ok(())
+    }
+
+    pub async fn report_external_task_failure(
+        &self,
+        external_task_id: &str,
+        error_message: &str,
+        error_details: Option<&str>,
+        retries: i32,
+        retry_timeout_ms: i64,
+    ) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint(&format!("external-task/{}/failure", external_task_id));
+        info!("Report failure for external task at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).json(&FailureRequest {
+            worker_id: &self.worker_id,
+            error_message,
+            error_details,
+            retries,
+            retry_timeout: retry_timeout_ms,
+        });
+        self.send(request, &endpoint, "Failure report").await?;
+
+        trace!("Task '{}' failure reported", external_task_id);
+        Ok(())
+    }
+
+    pub async fn report_bpmn_error(
+        &self,
+        external_task_id: &str,
+        error_code: &str,
+        error_message: Option<&str>,
+        variables: Option<OutputVariables>,
+    ) -> Result<(), OperatonError> {
+        let endpoint = self.endpoint(&format!("external-task/{}/bpmnError", external_task_id));
+        info!("Report BPMN error for external task at {}", endpoint);
+
+        let request = self.post(endpoint.clone()).json(&BpmnErrorRequest {
+            worker_id: &self.worker_id,
+            error_code,
+            error_message,
+            variables,
+        });
+        self.send(request, &endpoint, "BPMN error report").await?;
+
+        trace!("Task '{}' BPMN error reported", external_task_id);
+        Ok(())
+    }
 }
 
-pub async fn report_external_task_failure(
-    config: &ConfigParams,
-    external_task_id: &str,
-    error_message: &str,
-    error_details: Option<&str>,
-    retries: i32,
-    retry_timeout_ms: i64,
-) -> Result<(), Box<dyn Error>> {
-    let mut endpoint = config.url().clone();
-    let path_string = format!(
-        "engine-rest/external-task/{}/failure",
-        external_task_id
-    );
-    endpoint.set_path(path_string.as_str());
-    info!("Report failure for external task at {}", endpoint);
-
-    let client = reqwest::Client::new();
-    let request = build_authenticated_post(
-        &client,
-        endpoint.clone(),
-        config.username(),
-        config.password(),
-    )
-    .json(&FailureRequest {
-        worker_id: config.id(),
-        error_message,
-        error_details,
-        retries,
-        retry_timeout: retry_timeout_ms,
-    });
-
-    let response = request.send().await.map_err(|err| {
-        error!(
-            "Error while calling API endpoint '{}': {:#?}",
-            endpoint, err
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_keeps_base_path() {
+        let config = ConfigParams::default().with_url(Url::parse("https://host/operaton/").unwrap());
+        let client = OperatonClient::new(&config).unwrap();
+        assert_eq!(
+            client.endpoint("external-task/fetchAndLock").as_str(),
+            "https://host/operaton/engine-rest/external-task/fetchAndLock"
         );
-        err
-    })?;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_else(|_| "<no body>".to_string());
-        error!("Failure report failed: status={} body={} ", status, body);
-        return Err(format!("Failure report failed with status {status}").into());
+        let config = ConfigParams::default().with_url(Url::parse("http://localhost:8080").unwrap());
+        let client = OperatonClient::new(&config).unwrap();
+        assert_eq!(
+            client.endpoint("external-task/123/complete").as_str(),
+            "http://localhost:8080/engine-rest/external-task/123/complete"
+        );
     }
 
-    trace!("Task '{}' failure reported", external_task_id);
-    Ok(())
-}
+    #[test]
+    fn test_endpoint_with_rest_root_and_named_engine() {
+        let config = ConfigParams::default()
+            .with_url(Url::parse("https://host/operaton").unwrap())
+            .with_rest_root("api/rest/".to_string())
+            .with_engine_name(Some("tenant one".to_string()));
+        let client = OperatonClient::new(&config).unwrap();
+        assert_eq!(
+            client.endpoint("external-task/fetchAndLock").as_str(),
+            "https://host/operaton/api/rest/engine/tenant%20one/external-task/fetchAndLock"
+        );
+        assert_eq!(client.root_endpoint("version").as_str(), "https://host/operaton/api/rest/version");
+    }
 
-pub async fn report_bpmn_error(
-    config: &ConfigParams,
-    external_task_id: &str,
-    error_code: &str,
-    error_message: Option<&str>,
-    variables: Option<OutputVariables>,
-) -> Result<(), Box<dyn Error>> {
-    let mut endpoint = config.url().clone();
-    let path_string = format!(
-        "engine-rest/external-task/{}/bpmnError",
-        external_task_id
-    );
-    endpoint.set_path(path_string.as_str());
-    info!("Report BPMN error for external task at {}", endpoint);
-
-    let client = reqwest::Client::new();
-    let request = build_authenticated_post(
-        &client,
-        endpoint.clone(),
-        config.username(),
-        config.password(),
-    )
-    .json(&BpmnErrorRequest {
-        worker_id: config.id(),
-        error_code,
-        error_message,
-        variables,
-    });
-
-    let response = request.send().await.map_err(|err| {
-        error!(
-            "Error while calling API endpoint '{}': {:#?}",
-            endpoint, err
+    #[test]
+    fn test_topic_subscription_with_tenant_filter() {
+        let topic = TopicSubscription {
+            topic_name: "invoicing".to_string(),
+            lock_duration: 1_000,
+            variables: None,
+            local_variables: false,
+            tenant_id_in: Some(vec!["tenant-a".to_string()]),
+            without_tenant_id: false,
+            process_definition_key_in: None,
+            process_definition_version_tag: None,
+            business_key: None,
+            process_variables: None,
+            deserialize_values: false,
+        };
+        assert_eq!(
+            serde_json::to_value(&topic).unwrap(),
+            serde_json::json!({
+                "topicName": "invoicing",
+                "lockDuration": 1000,
+                "localVariables": false,
+                "tenantIdIn": ["tenant-a"],
+                "deserializeValues": false
+            })
+        );
+    }
+
+    fn retrying_client(server: &wiremock::MockServer) -> OperatonClient {
+        let config = ConfigParams::default()
+            .with_url(Url::parse(&server.uri()).unwrap())
+            .with_http_retry(2, 1, 1_000);
+        OperatonClient::new(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/extendLock"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/extendLock"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        retrying_client(&server).extend_lock("123", 1_000).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_limited_by_the_maximum_delay() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The engine asks for an hour, the client waits the maximum delay of 50 ms
+        let started = std::time::Instant::now();
+        let config = ConfigParams::default()
+            .with_url(Url::parse(&server.uri()).unwrap())
+            .with_http_retry(2, 1, 50);
+        OperatonClient::new(&config).unwrap().unlock_external_task("123").await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_without_maximum_delay() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Without a maximum delay, the client waits as long as the engine asks, up to a hard limit
+        let started = std::time::Instant::now();
+        let config = ConfigParams::default()
+            .with_url(Url::parse(&server.uri()).unwrap())
+            .with_http_retry(2, 1, 0);
+        OperatonClient::new(&config).unwrap().unlock_external_task("123").await.unwrap();
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_limited() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let err = retrying_client(&server).unlock_external_task("123").await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::BAD_GATEWAY));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token_is_refreshed_once() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        for token in ["revoked", "fresh"] {
+            Mock::given(method("POST"))
+                .and(path("/token"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "access_token": token })))
+                .up_to_n_times(1)
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .and(header("authorization", "Bearer revoked"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .and(header("authorization", "Bearer fresh"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default().with_url(Url::parse(&server.uri()).unwrap()).with_oauth2_client_credentials(
+            Url::parse(&format!("{}/token", server.uri())).unwrap(),
+            "worker".to_string(),
+            "secret".to_string(),
+            None,
         );
-        err
-    })?;
+        let client = OperatonClient::new(&config).unwrap();
+
+        client.unlock_external_task("123").await.unwrap();
+        // The fresh token is cached for the following requests
+        client.unlock_external_task("123").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejected_static_credentials_are_not_repeated() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/unlock"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default()
+            .with_url(Url::parse(&server.uri()).unwrap())
+            .with_auth("demo".to_string(), "wrong".to_string());
+        let client = OperatonClient::new(&config).unwrap();
+
+        let err = client.unlock_external_task("123").await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::UNAUTHORIZED));
+    }
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_else(|_| "<no body>".to_string());
-        error!("BPMN error report failed: status={} body={} ", status, body);
-        return Err(format!("BPMN error report failed with status {status}").into());
+    #[tokio::test]
+    async fn test_variable_instance_is_looked_up_in_the_scopes_of_the_task() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/process-instance/instance-1/activity-instances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "instance-1",
+                "childActivityInstances": [
+                    { "id": "SubProcess:1", "childActivityInstances": [{ "id": "ServiceTask_Ship:1", "childActivityInstances": [] }] },
+                    { "id": "ServiceTask_Bill:2", "childActivityInstances": [] }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/variable-instance"))
+            .and(query_param("variableName", "order"))
+            .and(query_param("activityInstanceIdIn", "ServiceTask_Ship:1,SubProcess:1,instance-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": "variable-1", "name": "order", "activityInstanceId": "instance-1", "type": "Json" },
+                { "id": "variable-2", "name": "order", "activityInstanceId": "SubProcess:1", "type": "Json" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/variable-instance"))
+            .and(query_param("variableName", "invoice"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": "variable-3", "name": "invoice", "activityInstanceId": "ServiceTask_Bill:2", "type": "File" }
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = OperatonClient::new(&ConfigParams::default().with_url(Url::parse(&server.uri()).unwrap())).unwrap();
+
+        // The variable of the enclosing sub process shadows the one of the process instance
+        let id = client.find_variable_instance_id("instance-1", Some("ServiceTask_Ship:1"), "order").await.unwrap();
+        assert_eq!(id.as_deref(), Some("variable-2"));
+
+        // A variable of a parallel branch is not visible to the task
+        let id = client.find_variable_instance_id("instance-1", Some("ServiceTask_Ship:1"), "invoice").await.unwrap();
+        assert_eq!(id, None);
+    }
+
+    #[tokio::test]
+    async fn test_task_not_found_is_not_retried() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/complete"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = retrying_client(&server).complete_external_task("123", OutputVariables::new()).await.unwrap_err();
+        assert!(err.is_not_found());
     }
 
-    trace!("Task '{}' BPMN error reported", external_task_id);
-    Ok(())
+    #[tokio::test]
+    async fn test_complete_with_local_variables() {
+        use crate::types::{out_bool, out_string};
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/123/complete"))
+            .and(body_json(serde_json::json!({
+                "workerId": "operaton_task_worker",
+                "variables": { "approved": { "value": true, "type": "Boolean", "valueInfo": {} } },
+                "localVariables": { "reviewer": { "value": "jane", "type": "String", "valueInfo": {} } }
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let variables = OutputVariables::from([
+            ("approved".to_string(), out_bool(true)),
+            ("reviewer".to_string(), out_string("jane").with_local(true)),
+        ]);
+        retrying_client(&server).complete_external_task("123", variables).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_engine_version() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/version"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "version": "1.0.0" })))
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default()
+            .with_url(Url::parse(&server.uri()).unwrap())
+            .with_engine_name(Some("other".to_string()));
+        let version = OperatonClient::new(&config).unwrap().engine_version().await.unwrap();
+        assert_eq!(version.version(), "1.0.0");
+        assert!(version.is_supported());
+    }
+
+    #[tokio::test]
+    async fn test_query_and_count_external_tasks() {
+        use crate::query::{ExternalTaskSortBy, SortOrder};
+        use wiremock::matchers::{body_json, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task"))
+            .and(query_param("maxResults", "1"))
+            .and(body_json(serde_json::json!({
+                "topicName": "invoicing",
+                "noRetriesLeft": true,
+                "sorting": [{ "sortBy": "createTime", "sortOrder": "asc" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "id": "123",
+                "topicName": "invoicing",
+                "workerId": null,
+                "lockExpirationTime": null,
+                "retries": 0,
+                "errorMessage": "Failed",
+                "suspended": false,
+                "priority": 0,
+                "activityId": "ServiceTask_SendInvoice",
+                "activityInstanceId": "ServiceTask_SendInvoice:1",
+                "executionId": "1",
+                "processInstanceId": "2",
+                "processDefinitionId": "Invoice:1:3",
+                "processDefinitionKey": "Invoice",
+                "tenantId": null
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/count"))
+            .and(body_json(serde_json::json!({ "topicName": "invoicing", "noRetriesLeft": true })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "count": 7 })))
+            .mount(&server)
+            .await;
+
+        let client = OperatonClient::new(&ConfigParams::default().with_url(Url::parse(&server.uri()).unwrap())).unwrap();
+        let query = ExternalTaskQuery::default()
+            .with_topic_name("invoicing")
+            .with_retries_left(false)
+            .with_sorting(ExternalTaskSortBy::CreateTime, SortOrder::Asc)
+            .with_paging(0, 1);
+
+        let tasks = client.query_external_tasks(&query).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].retries, Some(0));
+        assert!(!tasks[0].is_locked());
+        assert_eq!(client.count_external_tasks(&query).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_create_deployment() {
+        use crate::deployment::Deployment;
+        use wiremock::matchers::{body_string_contains, header_regex, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/deployment/create"))
+            .and(header_regex("content-type", "^multipart/form-data"))
+            .and(body_string_contains("name=\"deployment-name\"\r\n\r\ninvoice-worker"))
+            .and(body_string_contains("name=\"deploy-changed-only\"\r\n\r\ntrue"))
+            .and(body_string_contains("filename=\"invoice.bpmn\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "deployment-1",
+                "name": "invoice-worker",
+                "source": "operaton-task-worker",
+                "tenantId": null,
+                "deploymentTime": "2025-11-20T10:00:00.000+0000",
+                "deployedProcessDefinitions": { "Invoice:1:1": { "key": "Invoice" } },
+                "deployedDecisionDefinitions": null
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OperatonClient::new(&ConfigParams::default().with_url(Url::parse(&server.uri()).unwrap())).unwrap();
+        let deployment = Deployment::new("invoice-worker")
+            .with_source("operaton-task-worker")
+            .with_resource("invoice.bpmn", b"<definitions/>".to_vec());
+
+        let result = client.create_deployment(&deployment).await.unwrap();
+        assert_eq!(result.id, "deployment-1");
+        assert!(result.deployed_process_definitions.unwrap().contains_key("Invoice:1:1"));
+        assert_eq!(result.deployed_decision_definitions, None);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_retry_after_is_limited_without_maximum_delay() {
+        assert_eq!(limit_retry_after(Duration::from_secs(86_400), 0), Duration::from_millis(MAX_RETRY_AFTER_MS));
+        assert_eq!(limit_retry_after(Duration::from_secs(5), 0), Duration::from_secs(5));
+        assert_eq!(limit_retry_after(Duration::from_secs(5), 1_000), Duration::from_secs(1));
+    }
 }
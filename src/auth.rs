@@ -0,0 +1,317 @@
+//! This module includes the authentication providers for the Operaton REST API
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use log::{debug, trace};
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::error::OperatonError;
+use crate::settings::ConfigParams;
+
+/// Tokens are refreshed this long before they expire, so a request never carries an expired token
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// Adds authentication to the requests of the [crate::OperatonClient].
+///
+/// Implement this trait to plug in a custom authentication scheme.
+#[async_trait]
+pub trait AuthProvider: Send + Sync + std::fmt::Debug {
+    /// Add the authentication to a request
+    async fn authenticate(&self, request: RequestBuilder) -> Result<RequestBuilder, OperatonError>;
+
+    /// Drop cached credentials after the engine rejected them, so the next request uses fresh ones.
+    /// Returns whether the next request uses fresh credentials, only then the rejected request is repeated.
+    /// Static credentials cannot be refreshed, so the default does nothing and returns `false`.
+    async fn invalidate(&self) -> bool {
+        false
+    }
+}
+
+/// Anonymous access without authentication
+#[derive(Debug, Clone, Default)]
+pub struct NoAuth;
+
+#[async_trait]
+impl AuthProvider for NoAuth {
+    async fn authenticate(&self, request: RequestBuilder) -> Result<RequestBuilder, OperatonError> {
+        trace!("No HTTP authentication configured");
+        Ok(request)
+    }
+}
+
+/// HTTP Basic authentication
+#[derive(Clone)]
+pub struct BasicAuth {
+    username: String,
+    password: String,
+}
+
+impl BasicAuth {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self { username: username.into(), password: password.into() }
+    }
+}
+
+impl std::fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasicAuth").field("username", &self.username).finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for BasicAuth {
+    async fn authenticate(&self, request: RequestBuilder) -> Result<RequestBuilder, OperatonError> {
+        trace!("Using HTTP Basic authentication");
+        Ok(request.basic_auth(&self.username, Some(&self.password)))
+    }
+}
+
+/// A static bearer token
+#[derive(Clone)]
+pub struct BearerToken {
+    token: String,
+}
+
+impl BearerToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self { token: token.into() }
+    }
+}
+
+impl std::fmt::Debug for BearerToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BearerToken").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for BearerToken {
+    async fn authenticate(&self, request: RequestBuilder) -> Result<RequestBuilder, OperatonError> {
+        trace!("Using bearer token authentication");
+        Ok(request.bearer_auth(&self.token))
+    }
+}
+
+/// The OAuth2 client credentials flow. The access token is cached and refreshed before it expires.
+pub struct OAuth2ClientCredentials {
+    http: reqwest::Client,
+    token_url: url::Url,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    cached_token: Mutex<Option<CachedToken>>,
+}
+
+struct CachedToken {
+    access_token: String,
+    refresh_at: Option<Instant>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+impl OAuth2ClientCredentials {
+    pub fn new(
+        token_url: url::Url,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        scope: Option<String>,
+    ) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            token_url,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scope,
+            cached_token: Mutex::new(None),
+        }
+    }
+
+    /// Use an already configured HTTP client for the token requests
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Return the cached access token or fetch a new one if it is missing or about to expire
+    pub async fn access_token(&self) -> Result<String, OperatonError> {
+        let mut cached_token = self.cached_token.lock().await;
+
+        if let Some(token) = cached_token.as_ref()
+            && token.refresh_at.is_none_or(|refresh_at| Instant::now() < refresh_at)
+        {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = self.fetch_token().await?;
+        let access_token = token.access_token.clone();
+        *cached_token = Some(token);
+        Ok(access_token)
+    }
+
+    async fn fetch_token(&self) -> Result<CachedToken, OperatonError> {
+        debug!("Fetch OAuth2 access token at {}", self.token_url);
+
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        if let Some(scope) = &self.scope {
+            form.push(("scope", scope.as_str()));
+        }
+
+        let response = self.http.post(self.token_url.clone()).form(&form).send().await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(OperatonError::Authentication { status: Some(status), message: body });
+        }
+
+        let token: TokenResponse = serde_json::from_str(&body)?;
+        let refresh_at = token
+            .expires_in
+            .map(|expires_in| Instant::now() + Duration::from_secs(expires_in).saturating_sub(TOKEN_REFRESH_MARGIN));
+
+        Ok(CachedToken { access_token: token.access_token, refresh_at })
+    }
+}
+
+impl std::fmt::Debug for OAuth2ClientCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth2ClientCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for OAuth2ClientCredentials {
+    async fn authenticate(&self, request: RequestBuilder) -> Result<RequestBuilder, OperatonError> {
+        trace!("Using OAuth2 client credentials authentication");
+        Ok(request.bearer_auth(self.access_token().await?))
+    }
+
+    /// Drop the cached token, so the next request fetches a new one
+    async fn invalidate(&self) -> bool {
+        *self.cached_token.lock().await = None;
+        true
+    }
+}
+
+/// Build the authentication provider from the configuration.
+///
+/// OAuth2 client credentials take precedence over a bearer token, which takes precedence over HTTP Basic authentication.
+/// Without any of them, the requests are sent without authentication.
+pub fn auth_provider_from_config(config: &ConfigParams, http: &reqwest::Client) -> Arc<dyn AuthProvider> {
+    if let Some(token_url) = config.oauth2_token_url() {
+        Arc::new(
+            OAuth2ClientCredentials::new(
+                token_url.clone(),
+                config.oauth2_client_id(),
+                config.oauth2_client_secret(),
+                config.oauth2_scope().map(str::to_string),
+            )
+            .with_http_client(http.clone()),
+        )
+    } else if !config.bearer_token().is_empty() {
+        Arc::new(BearerToken::new(config.bearer_token()))
+    } else if !config.username().is_empty() {
+        Arc::new(BasicAuth::new(config.username(), config.password()))
+    } else {
+        Arc::new(NoAuth)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_oauth2_token_is_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(body_string_contains("client_id=worker"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "token-1",
+                "token_type": "Bearer",
+                "expires_in": 300
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = OAuth2ClientCredentials::new(
+            url::Url::parse(&format!("{}/token", server.uri())).unwrap(),
+            "worker",
+            "secret",
+            Some("operaton".to_string()),
+        );
+
+        assert_eq!(provider.access_token().await.unwrap(), "token-1");
+        assert_eq!(provider.access_token().await.unwrap(), "token-1");
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_token_is_refreshed_when_expired() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "short-lived",
+                "expires_in": 10
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let provider = OAuth2ClientCredentials::new(
+            url::Url::parse(&format!("{}/token", server.uri())).unwrap(),
+            "worker",
+            "secret",
+            None,
+        );
+
+        // The token expires within the refresh margin, so every request fetches a new one
+        provider.access_token().await.unwrap();
+        provider.access_token().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_token_endpoint_rejects_credentials() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": "invalid_client"
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = OAuth2ClientCredentials::new(
+            url::Url::parse(&format!("{}/token", server.uri())).unwrap(),
+            "worker",
+            "wrong",
+            None,
+        );
+
+        let err = provider.access_token().await.unwrap_err();
+        assert!(matches!(err, OperatonError::Authentication { .. }));
+        assert_eq!(err.status(), Some(reqwest::StatusCode::UNAUTHORIZED));
+    }
+}
@@ -0,0 +1,234 @@
+//! This module includes the context which is passed to the task handlers
+
+use tokio::runtime::Handle;
+
+use crate::api::OperatonClient;
+use crate::engine::{MessageCorrelation, ProcessInstance, ProcessStart, Signal};
+use crate::error::OperatonError;
+use crate::structures::process_variables::ProcessInstanceVariable;
+use crate::structures::service_task::ServiceTask;
+
+/// The context of the external task a handler is executed for.
+///
+/// Handlers registered with `#[task_handler]` receive it as optional second argument:
+///
+/// ```ignore
+/// #[task_handler(name = "ServiceTask_ID")]
+/// fn handler(input: &InputVariables, context: &TaskContext) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+///     let credentials = credentials_for_tenant(context.tenant_id());
+///     context.correlate_message(&MessageCorrelation::new("OrderShipped").with_business_key("order-4711"))?;
+///     ...
+/// }
+/// ```
+///
+/// The engine calls of the context use the worker's REST client. They block until the engine answered,
+/// which is fine because handlers are executed on Tokio's blocking thread pool.
+#[derive(Clone, Debug)]
+pub struct TaskContext {
+    task: ServiceTask,
+    engine: Option<(OperatonClient, Handle)>,
+}
+
+impl TaskContext {
+    /// A context without connection to the engine, e.g. for testing handlers
+    pub fn new(task: ServiceTask) -> Self {
+        Self { task, engine: None }
+    }
+
+    /// A context whose engine calls use the given client on the current Tokio runtime.
+    /// Panics if it is not called within a Tokio runtime.
+    pub fn with_client(task: ServiceTask, client: OperatonClient) -> Self {
+        Self { task, engine: Some((client, Handle::current())) }
+    }
+
+    /// The fetched external task
+    pub fn task(&self) -> &ServiceTask {
+        &self.task
+    }
+
+    /// The external task id
+    pub fn task_id(&self) -> &str {
+        self.task.id()
+    }
+
+    pub fn activity_id(&self) -> &str {
+        self.task.activity_id()
+    }
+
+    pub fn topic_name(&self) -> &str {
+        self.task.topic_name()
+    }
+
+    pub fn process_instance_id(&self) -> &str {
+        self.task.process_instance_id()
+    }
+
+    pub fn business_key(&self) -> Option<String> {
+        self.task.business_key()
+    }
+
+    /// The tenant of the task, `None` if the task does not belong to a tenant
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.task.tenant_id()
+    }
+
+    fn engine(&self) -> Result<&(OperatonClient, Handle), OperatonError> {
+        self.engine.as_ref().ok_or_else(|| {
+            OperatonError::Configuration("The task context is not connected to an engine".to_string())
+        })
+    }
+
+    /// The content of a File or Bytes variable of the task, which is downloaded from the engine unless it was fetched
+    /// with the task
+    pub fn variable_data(&self, name: &str) -> Result<Vec<u8>, OperatonError> {
+        let variables = self.task.variables();
+        let variable = variables
+            .get(name)
+            .filter(|variable| matches!(variable, ProcessInstanceVariable::File(_) | ProcessInstanceVariable::Bytes(_)))
+            .ok_or_else(|| OperatonError::Configuration(format!("The task has no File or Bytes variable {}", name)))?;
+        if let Some(content) = variable.as_bytes() {
+            return Ok(content.to_vec());
+        }
+
+        let (client, runtime) = self.engine()?;
+        runtime.block_on(async {
+            let variable_instance_id = match variable.variable_instance_id() {
+                Some(id) => id.to_string(),
+                None => client
+                    .find_variable_instance_id(self.process_instance_id(), self.task.activity_instance_id(), name)
+                    .await?
+                    .ok_or_else(|| OperatonError::Configuration(format!("The variable {} does not exist anymore", name)))?,
+            };
+            client.variable_instance_data(&variable_instance_id).await
+        })
+    }
+
+    /// Correlate a message with a waiting process instance or a message start event
+    pub fn correlate_message(&self, message: &MessageCorrelation) -> Result<(), OperatonError> {
+        let (client, runtime) = self.engine()?;
+        runtime.block_on(client.correlate_message(message))
+    }
+
+    /// Send a signal to all process instances waiting for it
+    pub fn send_signal(&self, signal: &Signal) -> Result<(), OperatonError> {
+        let (client, runtime) = self.engine()?;
+        runtime.block_on(client.send_signal(signal))
+    }
+
+    /// Start a process instance, e.g. a follow-up process
+    pub fn start_process_instance(&self, start: &ProcessStart) -> Result<ProcessInstance, OperatonError> {
+        let (client, runtime) = self.engine()?;
+        runtime.block_on(client.start_process_instance(start))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::settings::ConfigParams;
+    use crate::types::{out_bytes, out_string};
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn service_task() -> ServiceTask {
+        serde_json::from_value(serde_json::json!({
+            "id": "task-1",
+            "activityId": "ServiceTask_Ship",
+            "processInstanceId": "instance-1",
+            "topicName": "shipping",
+            "priority": 0,
+            "businessKey": "order-4711",
+            "workerId": "operaton_task_worker"
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_engine_calls_from_a_handler() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/message"))
+            .and(body_json(serde_json::json!({ "messageName": "OrderShipped", "businessKey": "order-4711" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/process-definition/key/Invoice/start"))
+            .and(body_json(serde_json::json!({
+                "businessKey": "order-4711",
+                "variables": { "orderId": { "value": "4711", "type": "String", "valueInfo": {} } }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "instance-2",
+                "definitionId": "Invoice:1:1",
+                "businessKey": "order-4711",
+                "tenantId": null,
+                "ended": false,
+                "suspended": false
+            })))
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default().with_url(url::Url::parse(&server.uri()).unwrap());
+        let context = TaskContext::with_client(service_task(), OperatonClient::new(&config).unwrap());
+
+        // Handlers run on the blocking thread pool, where the engine calls block on the runtime
+        let process_instance = tokio::task::spawn_blocking(move || {
+            let business_key = context.business_key().unwrap();
+            context.correlate_message(&MessageCorrelation::new("OrderShipped").with_business_key(&business_key))?;
+            context.start_process_instance(
+                &ProcessStart::new("Invoice")
+                    .with_business_key(business_key)
+                    .with_variable("orderId", out_string("4711")),
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(process_instance.id, "instance-2");
+    }
+
+    #[tokio::test]
+    async fn test_download_file_variable() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/variable-instance"))
+            .and(query_param("variableName", "invoice"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": "variable-1", "name": "invoice", "activityInstanceId": "instance-1", "type": "File" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/variable-instance/variable-1/data"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"%PDF-1.7".to_vec()))
+            .mount(&server)
+            .await;
+
+        let mut task = serde_json::to_value(service_task()).unwrap();
+        task["variables"] = serde_json::json!({
+            "invoice": { "type": "File", "value": null, "valueInfo": { "filename": "invoice.pdf", "mimeType": "application/pdf" } },
+            "signature": serde_json::to_value(out_bytes(&[1, 2, 3])).unwrap()
+        });
+        let config = ConfigParams::default().with_url(url::Url::parse(&server.uri()).unwrap());
+        let context = TaskContext::with_client(serde_json::from_value(task).unwrap(), OperatonClient::new(&config).unwrap());
+
+        let (invoice, signature) = tokio::task::spawn_blocking(move || {
+            (context.variable_data("invoice").unwrap(), context.variable_data("signature").unwrap())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(invoice, b"%PDF-1.7");
+        assert_eq!(signature, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_context_without_engine() {
+        let context = TaskContext::new(service_task());
+        let err = context.send_signal(&Signal::new("Alarm")).unwrap_err();
+        assert!(matches!(err, OperatonError::Configuration(_)));
+    }
+}
@@ -0,0 +1,178 @@
+//! This module includes the deployment of BPMN, DMN and form resources
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// The file extensions of the resources which are deployed from a directory
+const DEPLOYABLE_EXTENSIONS: [&str; 3] = ["bpmn", "dmn", "form"];
+
+/// A deployment of resources via `/deployment/create`.
+///
+/// By default, the engine filters duplicates and only deploys changed resources, so deploying the same resources on
+/// every start of the worker does not create new versions of the process definitions.
+///
+/// ```rust
+/// use operaton_task_worker::deployment::Deployment;
+///
+/// let deployment = Deployment::new("invoice-worker")
+///     .with_source("invoice-worker 1.2.0")
+///     .with_resource("invoice.bpmn", b"<definitions/>".to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Deployment {
+    name: String,
+    source: Option<String>,
+    tenant_id: Option<String>,
+    enable_duplicate_filtering: bool,
+    deploy_changed_only: bool,
+    resources: Vec<(String, Vec<u8>)>,
+}
+
+impl Deployment {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: None,
+            tenant_id: None,
+            enable_duplicate_filtering: true,
+            deploy_changed_only: true,
+            resources: Vec::new(),
+        }
+    }
+
+    /// A deployment of all `.bpmn`, `.dmn` and `.form` files in a directory and its subdirectories.
+    /// The resources are named by their path relative to the directory.
+    pub fn from_directory(name: impl Into<String>, directory: &Path) -> io::Result<Self> {
+        let mut deployment = Self::new(name);
+        let mut files = Vec::new();
+        collect_files(directory, &mut files)?;
+        files.sort();
+
+        for file in files {
+            let resource_name = file
+                .strip_prefix(directory)
+                .unwrap_or(&file)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            deployment.resources.push((resource_name, std::fs::read(&file)?));
+        }
+
+        Ok(deployment)
+    }
+
+    pub fn with_source(self, source: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.source = Some(source.into());
+        cloned_self
+    }
+
+    pub fn with_tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tenant_id = Some(tenant_id.into());
+        cloned_self
+    }
+
+    /// Whether the engine skips the deployment if the resources did not change since the last deployment with the same name
+    pub fn with_duplicate_filtering(self, enable_duplicate_filtering: bool, deploy_changed_only: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.enable_duplicate_filtering = enable_duplicate_filtering;
+        cloned_self.deploy_changed_only = deploy_changed_only;
+        cloned_self
+    }
+
+    pub fn with_resource(self, name: impl Into<String>, content: Vec<u8>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.resources.push((name.into(), content));
+        cloned_self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the resources
+    pub fn resource_names(&self) -> Vec<&str> {
+        self.resources.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// The multipart form of the `/deployment/create` request
+    pub(crate) fn form(&self) -> reqwest::multipart::Form {
+        let mut form = reqwest::multipart::Form::new()
+            .text("deployment-name", self.name.clone())
+            .text("enable-duplicate-filtering", self.enable_duplicate_filtering.to_string())
+            .text("deploy-changed-only", self.deploy_changed_only.to_string());
+        if let Some(source) = &self.source {
+            form = form.text("deployment-source", source.clone());
+        }
+        if let Some(tenant_id) = &self.tenant_id {
+            form = form.text("tenant-id", tenant_id.clone());
+        }
+        for (name, content) in &self.resources {
+            form = form.part(name.clone(), reqwest::multipart::Part::bytes(content.clone()).file_name(name.clone()));
+        }
+        form
+    }
+}
+
+fn collect_files(directory: &Path, files: &mut Vec<std::path::PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| DEPLOYABLE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The result of a deployment
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentResult {
+    pub id: String,
+    pub name: Option<String>,
+    pub source: Option<String>,
+    pub tenant_id: Option<String>,
+    pub deployment_time: Option<String>,
+    /// The process definitions created by the deployment by id, `None` if no resource changed
+    #[serde(default)]
+    pub deployed_process_definitions: Option<HashMap<String, serde_json::Value>>,
+    /// The decision definitions created by the deployment by id, `None` if no resource changed
+    #[serde(default)]
+    pub deployed_decision_definitions: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_deployment_from_directory() {
+        let directory = std::env::temp_dir().join(format!("operaton-task-worker-deployment-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("decisions")).unwrap();
+        std::fs::write(directory.join("invoice.bpmn"), "<definitions/>").unwrap();
+        std::fs::write(directory.join("decisions").join("approval.DMN"), "<definitions/>").unwrap();
+        std::fs::write(directory.join("README.md"), "not deployed").unwrap();
+
+        let deployment = Deployment::from_directory("invoice-worker", &directory).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(deployment.name(), "invoice-worker");
+        assert_eq!(deployment.resource_names(), ["decisions/approval.DMN", "invoice.bpmn"]);
+    }
+
+    #[test]
+    fn test_missing_directory() {
+        assert!(Deployment::from_directory("invoice-worker", Path::new("/does/not/exist")).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! This module includes the requests for interacting with processes: correlating messages, sending signals and
+//! starting process instances
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{OutVariable, OutputVariables};
+
+/// A message to correlate with a waiting process instance or a message start event
+///
+/// ```rust
+/// use operaton_task_worker::engine::MessageCorrelation;
+/// use operaton_task_worker::types::out_string;
+///
+/// let message = MessageCorrelation::new("PaymentReceived")
+///     .with_business_key("order-4711")
+///     .with_process_variable("paymentId", out_string("p-1"));
+/// ```
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCorrelation {
+    message_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    business_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process_instance_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tenant_id: Option<String>,
+    #[serde(skip_serializing_if = "OutputVariables::is_empty")]
+    correlation_keys: OutputVariables,
+    #[serde(skip_serializing_if = "OutputVariables::is_empty")]
+    process_variables: OutputVariables,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    all: bool,
+}
+
+impl MessageCorrelation {
+    pub fn new(message_name: impl Into<String>) -> Self {
+        Self { message_name: message_name.into(), ..Default::default() }
+    }
+
+    pub fn with_business_key(self, business_key: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.business_key = Some(business_key.into());
+        cloned_self
+    }
+
+    pub fn with_process_instance_id(self, process_instance_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.process_instance_id = Some(process_instance_id.into());
+        cloned_self
+    }
+
+    pub fn with_tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tenant_id = Some(tenant_id.into());
+        cloned_self
+    }
+
+    /// Only correlate with process instances which have a variable with this value
+    pub fn with_correlation_key(self, name: impl Into<String>, value: OutVariable) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.correlation_keys.insert(name.into(), value);
+        cloned_self
+    }
+
+    /// Set a variable in the process instance the message is correlated with
+    pub fn with_process_variable(self, name: impl Into<String>, value: OutVariable) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.process_variables.insert(name.into(), value);
+        cloned_self
+    }
+
+    /// Correlate the message with all matching process instances instead of exactly one
+    pub fn with_all(self, all: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.all = all;
+        cloned_self
+    }
+}
+
+/// A signal which is delivered to all process instances waiting for it
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Signal {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    execution_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tenant_id: Option<String>,
+    #[serde(skip_serializing_if = "OutputVariables::is_empty")]
+    variables: OutputVariables,
+}
+
+impl Signal {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), ..Default::default() }
+    }
+
+    /// Only deliver the signal to this execution
+    pub fn with_execution_id(self, execution_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.execution_id = Some(execution_id.into());
+        cloned_self
+    }
+
+    pub fn with_tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tenant_id = Some(tenant_id.into());
+        cloned_self
+    }
+
+    pub fn with_variable(self, name: impl Into<String>, value: OutVariable) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.variables.insert(name.into(), value);
+        cloned_self
+    }
+}
+
+/// The start of a process instance of the latest version of a process definition
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStart {
+    #[serde(skip)]
+    process_definition_key: String,
+    #[serde(skip)]
+    tenant_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    business_key: Option<String>,
+    #[serde(skip_serializing_if = "OutputVariables::is_empty")]
+    variables: OutputVariables,
+}
+
+impl ProcessStart {
+    pub fn new(process_definition_key: impl Into<String>) -> Self {
+        Self { process_definition_key: process_definition_key.into(), ..Default::default() }
+    }
+
+    /// Start the process definition of this tenant
+    pub fn with_tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tenant_id = Some(tenant_id.into());
+        cloned_self
+    }
+
+    pub fn with_business_key(self, business_key: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.business_key = Some(business_key.into());
+        cloned_self
+    }
+
+    pub fn with_variable(self, name: impl Into<String>, value: OutVariable) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.variables.insert(name.into(), value);
+        cloned_self
+    }
+
+    /// The path of the start resource relative to the engine
+    pub(crate) fn path(&self) -> String {
+        match &self.tenant_id {
+            Some(tenant_id) => format!("process-definition/key/{}/tenant-id/{}/start", self.process_definition_key, tenant_id),
+            None => format!("process-definition/key/{}/start", self.process_definition_key),
+        }
+    }
+}
+
+/// A started process instance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInstance {
+    pub id: String,
+    pub definition_id: Option<String>,
+    pub business_key: Option<String>,
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub ended: bool,
+    #[serde(default)]
+    pub suspended: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::out_string;
+
+    #[test]
+    fn test_message_correlation_body() {
+        let message = MessageCorrelation::new("PaymentReceived")
+            .with_business_key("order-4711")
+            .with_correlation_key("orderId", out_string("4711"));
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({
+                "messageName": "PaymentReceived",
+                "businessKey": "order-4711",
+                "correlationKeys": { "orderId": { "value": "4711", "type": "String", "valueInfo": {} } }
+            })
+        );
+    }
+
+    #[test]
+    fn test_process_start_path() {
+        assert_eq!(ProcessStart::new("Invoice").path(), "process-definition/key/Invoice/start");
+        assert_eq!(
+            ProcessStart::new("Invoice").with_tenant_id("tenant-a").path(),
+            "process-definition/key/Invoice/tenant-id/tenant-a/start"
+        );
+        assert_eq!(serde_json::to_value(ProcessStart::new("Invoice")).unwrap(), serde_json::json!({}));
+    }
+}
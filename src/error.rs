@@ -0,0 +1,198 @@
+//! This module includes the error type of the Operaton REST client
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// The error body returned by the Operaton REST API for non-successful requests
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EngineError {
+    /// The exception type, e.g. `RestException` or `InvalidRequestException`
+    #[serde(rename = "type", default)]
+    pub error_type: String,
+
+    /// The exception message
+    #[serde(default)]
+    pub message: String,
+
+    /// The error code (available in Operaton and Camunda 7.20+)
+    #[serde(default)]
+    pub code: Option<i64>,
+}
+
+impl EngineError {
+    /// Parse the error body of a response. Bodies which are not an Operaton error object are kept as message.
+    pub fn from_body(body: &str) -> Self {
+        serde_json::from_str(body).unwrap_or_else(|_| EngineError {
+            message: body.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+/// The error type of all calls to the Operaton REST API
+#[derive(Debug)]
+pub enum OperatonError {
+    /// The request could not be sent or the connection failed
+    Transport(reqwest::Error),
+
+    /// The request did not complete within the configured timeout
+    Timeout(reqwest::Error),
+
+    /// The response body could not be deserialized
+    Deserialization(serde_json::Error),
+
+    /// The engine answered with a non-successful HTTP status
+    Engine {
+        status: StatusCode,
+        error: EngineError,
+    },
+
+    /// The authentication provider could not authenticate the request, e.g. the token endpoint rejected the credentials
+    Authentication {
+        status: Option<StatusCode>,
+        message: String,
+    },
+
+    /// The configuration is invalid, e.g. a certificate file could not be read
+    Configuration(String),
+}
+
+impl OperatonError {
+    /// The HTTP status of an engine error
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            OperatonError::Engine { status, .. } => Some(*status),
+            OperatonError::Authentication { status, .. } => *status,
+            OperatonError::Transport(err) | OperatonError::Timeout(err) => err.status(),
+            OperatonError::Deserialization(_) | OperatonError::Configuration(_) => None,
+        }
+    }
+
+    /// The parsed error body of an engine error
+    pub fn engine_error(&self) -> Option<&EngineError> {
+        match self {
+            OperatonError::Engine { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Whether the requested resource (e.g. the external task) does not exist (anymore)
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Whether the external task is locked by another worker
+    pub fn is_locked_by_other_worker(&self) -> bool {
+        match self {
+            OperatonError::Engine { status, error } => {
+                status.is_client_error() && error.message.contains("locked by worker")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the request timed out
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, OperatonError::Timeout(_))
+    }
+
+    /// Whether the request failed for a reason which may go away, so it can be sent again: the connection failed,
+    /// the request timed out, or the engine (or a load balancer in front of it) is overloaded or unavailable
+    pub fn is_transient(&self) -> bool {
+        match self {
+            OperatonError::Transport(_) | OperatonError::Timeout(_) => true,
+            OperatonError::Engine { status, .. } => matches!(
+                *status,
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether the engine could not be reached or the connection failed
+    pub fn is_transport(&self) -> bool {
+        matches!(self, OperatonError::Transport(_))
+    }
+}
+
+impl std::fmt::Display for OperatonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperatonError::Transport(err) => write!(f, "Transport error: {err}"),
+            OperatonError::Timeout(err) => write!(f, "Request timed out: {err}"),
+            OperatonError::Deserialization(err) => write!(f, "Could not deserialize the response: {err}"),
+            OperatonError::Engine { status, error } if error.error_type.is_empty() => {
+                write!(f, "Engine error (status {status}): {}", error.message)
+            }
+            OperatonError::Engine { status, error } => {
+                write!(f, "Engine error (status {status}) {}: {}", error.error_type, error.message)
+            }
+            OperatonError::Authentication { status: Some(status), message } => {
+                write!(f, "Authentication failed (status {status}): {message}")
+            }
+            OperatonError::Authentication { status: None, message } => write!(f, "Authentication failed: {message}"),
+            OperatonError::Configuration(message) => write!(f, "Invalid configuration: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for OperatonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OperatonError::Transport(err) | OperatonError::Timeout(err) => Some(err),
+            OperatonError::Deserialization(err) => Some(err),
+            OperatonError::Engine { .. } | OperatonError::Authentication { .. } | OperatonError::Configuration(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for OperatonError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            OperatonError::Timeout(err)
+        } else {
+            OperatonError::Transport(err)
+        }
+    }
+}
+
+impl From<serde_json::Error> for OperatonError {
+    fn from(err: serde_json::Error) -> Self {
+        OperatonError::Deserialization(err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_engine_error_from_body() {
+        let body = r#"{"type":"InvalidRequestException","message":"External Task 123 cannot be completed by worker 'a'. It is locked by worker 'b'.","code":0}"#;
+        let error = EngineError::from_body(body);
+        assert_eq!(error.error_type, "InvalidRequestException");
+        assert_eq!(error.code, Some(0));
+
+        let err = OperatonError::Engine { status: StatusCode::BAD_REQUEST, error };
+        assert!(err.is_locked_by_other_worker());
+        assert!(!err.is_not_found());
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn test_engine_error_from_non_json_body() {
+        let error = EngineError::from_body("Bad Gateway");
+        assert_eq!(error.error_type, "");
+        assert_eq!(error.message, "Bad Gateway");
+        assert_eq!(error.code, None);
+
+        let err = OperatonError::Engine { status: StatusCode::NOT_FOUND, error };
+        assert!(err.is_not_found());
+        assert!(!err.is_locked_by_other_worker());
+        assert!(!err.is_transient());
+        assert_eq!(err.to_string(), "Engine error (status 404 Not Found): Bad Gateway");
+    }
+}
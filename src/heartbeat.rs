@@ -0,0 +1,94 @@
+//! This module includes the heartbeat which keeps the lock of a running task alive
+
+use log::{debug, error, warn};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::api::OperatonClient;
+
+/// Extends the lock of an external task periodically while its handler is running.
+///
+/// The lock is extended by the full lock duration every half lock duration, so a single failed
+/// extension does not let the lock expire.
+pub struct LockHeartbeat {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl LockHeartbeat {
+    /// Start the heartbeat for an external task which is locked for `lock_duration` milliseconds
+    pub fn start(client: OperatonClient, external_task_id: String, lock_duration: u64) -> Self {
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let interval = heartbeat_interval(lock_duration);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+                // A stop abandons a running extension, so its retries do not hold back the result of the task
+                let result = tokio::select! {
+                    _ = &mut stopped => break,
+                    result = client.extend_lock(&external_task_id, lock_duration) => result,
+                };
+                match result {
+                    Ok(()) => debug!("Extended lock of external task {} by {} ms", external_task_id, lock_duration),
+                    Err(err) if err.is_not_found() || err.is_locked_by_other_worker() => {
+                        warn!("Stop extending the lock of external task {}: {}", external_task_id, err);
+                        break;
+                    }
+                    Err(err) => error!("Could not extend the lock of external task {}: {}", external_task_id, err),
+                }
+            }
+        });
+
+        Self { stop, handle }
+    }
+
+    /// Stop the heartbeat and wait until it has terminated. A running lock extension is abandoned.
+    pub async fn stop(self) {
+        // The receiver is gone if the heartbeat already stopped itself
+        let _ = self.stop.send(());
+        if let Err(err) = self.handle.await {
+            error!("Lock heartbeat terminated abnormally: {:#?}", err);
+        }
+    }
+}
+
+fn heartbeat_interval(lock_duration: u64) -> std::time::Duration {
+    std::time::Duration::from_millis((lock_duration / 2).max(1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_interval_is_half_the_lock_duration() {
+        assert_eq!(heartbeat_interval(60_000), std::time::Duration::from_millis(30_000));
+        assert_eq!(heartbeat_interval(0), std::time::Duration::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn test_stop_does_not_wait_for_a_running_extension() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/extendLock"))
+            .respond_with(ResponseTemplate::new(204).set_delay(std::time::Duration::from_secs(10)))
+            .mount(&server)
+            .await;
+
+        let config = crate::settings::ConfigParams::default().with_url(url::Url::parse(&server.uri()).unwrap());
+        let heartbeat = LockHeartbeat::start(OperatonClient::new(&config).unwrap(), "task-1".to_string(), 20);
+
+        // Stop the heartbeat while the engine is still answering the first extension
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), heartbeat.stop())
+            .await
+            .expect("the heartbeat should stop without waiting for the extension");
+    }
+}
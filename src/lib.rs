@@ -13,6 +13,11 @@ The crate uses the Operaton API to poll for external tasks and execute them via
 The crate is tested with Operaton 1.0 and intends to provide a stable abstraction layer for future Operaton versions.
 Camunda 7 is not supported, however, at the current state, it should be possible to use the crate with Camunda 7.
 
+At startup, the worker calls `GET /version` and logs the engine and its version. The check accepts Operaton 1.x and,
+as it should work as well, Camunda 7.20 or later. If the engine is unreachable, rejects the credentials or runs an unsupported version, the worker
+logs a warning, or stops with `startup_check = fail`. Client credentials rejected by the OAuth2 token endpoint are
+reported as such, not as rejected by the engine. The probe is available as `OperatonClient::engine_version`.
+
 ## How to Use this Crate
 
 Running a task worker with this crate is intended to be very easy and involves two steps:
@@ -55,12 +60,53 @@ You can also load the configuration from environment variables using the `load_c
 #### Using the environment variables
 
 The following environment variables are used by the task worker--given that the prefix is `OPERATON_TASK_WORKER`:
-- `OPERATON_TASK_WORKER_URL` - URL of the Operaton Task Service
+- `OPERATON_TASK_WORKER_URL` - URL of the Operaton Task Service; a path (e.g. `https://host/operaton/`) is preserved
+- `OPERATON_TASK_WORKER_REST_ROOT` - Path of the REST API relative to the URL (default: `engine-rest`)
+- `OPERATON_TASK_WORKER_ENGINE_NAME` - Name of the process engine to address via `/engine/{name}/...` (default: the default engine)
 - `OPERATON_TASK_WORKER_USERNAME` - Username for the Operaton Task Service (leave empty for anonymous access)
 - `OPERATON_TASK_WORKER_PASSWORD` - Password for the Operaton Task Service (leave empty for anonymous access)
+- `OPERATON_TASK_WORKER_BEARER_TOKEN` - Static bearer token for the Operaton Task Service (takes precedence over username and password)
+- `OPERATON_TASK_WORKER_OAUTH2_TOKEN_URL` - Token endpoint for the OAuth2 client credentials flow (takes precedence over all other authentication settings)
+- `OPERATON_TASK_WORKER_OAUTH2_CLIENT_ID` - Client id for the OAuth2 client credentials flow
+- `OPERATON_TASK_WORKER_OAUTH2_CLIENT_SECRET` - Client secret for the OAuth2 client credentials flow
+- `OPERATON_TASK_WORKER_OAUTH2_SCOPE` - Scope requested with the OAuth2 client credentials flow (optional)
 - `OPERATON_TASK_WORKER_POLL_INTERVAL` - Interval in milliseconds for polling the Operaton Task Service for new tasks
 - `OPERATON_TASK_WORKER_ID` - The task worker id which will be registered with Operaton
 - `OPERATON_TASK_WORKER_LOCK_DURATION` - Duration in milliseconds to lock an external task when picked up by this worker (default: 60000)
+- `OPERATON_TASK_WORKER_HTTP_MAX_RETRIES` - Number of retries of a request which failed with a transient error (default: 3)
+- `OPERATON_TASK_WORKER_HTTP_RETRY_DELAY` - Initial delay in milliseconds before retrying a request (default: 200)
+- `OPERATON_TASK_WORKER_HTTP_RETRY_MAX_DELAY` - Maximum delay in milliseconds before retrying a request, `0` for no limit except at most one minute for `Retry-After` (default: 10000)
+- `OPERATON_TASK_WORKER_OUTBOX_PATH` - File of the durable outbox for task results (default: no outbox)
+- `OPERATON_TASK_WORKER_OUTBOX_RETRY_DELAY` - Initial delay in milliseconds between attempts to send a result from the outbox (default: 1000)
+- `OPERATON_TASK_WORKER_OUTBOX_RETRY_MAX_DELAY` - Maximum delay in milliseconds between attempts to send a result from the outbox (default: 60000)
+- `OPERATON_TASK_WORKER_STARTUP_CHECK` - What to do if the engine check at startup fails: `fail`, `warn` or `off` (default: `warn`)
+- `OPERATON_TASK_WORKER_DEPLOYMENT_DIR` - Directory with `.bpmn`, `.dmn` and `.form` files to deploy at startup (default: no deployment)
+- `OPERATON_TASK_WORKER_DEPLOYMENT_NAME` - Name of the deployment (default: the worker id)
+- `OPERATON_TASK_WORKER_DEPLOYMENT_SOURCE` - Source of the deployment (default: `operaton-task-worker`)
+- `OPERATON_TASK_WORKER_DEPLOY_CHANGED_ONLY` - Whether only changed resources are deployed again (default: true)
+- `OPERATON_TASK_WORKER_AUTO_EXTEND_LOCK` - Extend the lock of fetched tasks periodically until their handler finishes (default: true)
+- `OPERATON_TASK_WORKER_UNHANDLED_TASK_POLICY` - What to do with a fetched task without a handler: `unlock`, `failure` or `incident` (default: `unlock`)
+- `OPERATON_TASK_WORKER_STRICT_VARIABLES` - Fail a task with an incident if its variables cannot be parsed, instead of running the handler without them (default: false)
+- `OPERATON_TASK_WORKER_MAX_RETRIES` - Number of retries of a failed task before an incident is created (default: 0)
+- `OPERATON_TASK_WORKER_RETRY_BACKOFF` - Backoff between retries: `fixed`, `linear` or `exponential` (default: `fixed`)
+- `OPERATON_TASK_WORKER_RETRY_DELAY` - Base delay in milliseconds between retries (default: 10000)
+- `OPERATON_TASK_WORKER_RETRY_MAX_DELAY` - Maximum delay in milliseconds between retries, `0` for no limit (default: 0)
+- `OPERATON_TASK_WORKER_MAX_TASKS` - Maximum number of tasks fetched and locked in one request (default: 10)
+- `OPERATON_TASK_WORKER_USE_PRIORITY` - Fetch tasks with a higher priority first (default: false)
+- `OPERATON_TASK_WORKER_TENANT_ID_IN` - Comma separated list of tenants whose tasks are fetched (default: all tenants)
+- `OPERATON_TASK_WORKER_WITHOUT_TENANT_ID` - Only fetch tasks which do not belong to a tenant (default: false)
+- `OPERATON_TASK_WORKER_SUBSCRIPTION_FILTERS` - JSON object of filters by topic, e.g. `{"invoicing": {"process_definition_key_in": ["Invoice"]}}`; replaces the filters of the topic's handlers
+- `OPERATON_TASK_WORKER_ASYNC_RESPONSE_TIMEOUT` - Long polling timeout in milliseconds; the engine holds the fetch request open until tasks arrive (default: 0, long polling disabled)
+- `OPERATON_TASK_WORKER_CONNECT_TIMEOUT` - Timeout in milliseconds for connecting to the REST API (default: 10000)
+- `OPERATON_TASK_WORKER_REQUEST_TIMEOUT` - Timeout in milliseconds for a request to the REST API, `0` disables it (default: 30000)
+- `OPERATON_TASK_WORKER_TCP_KEEPALIVE` - TCP keep-alive interval in milliseconds, `0` disables it (default: 60000)
+- `OPERATON_TASK_WORKER_POOL_IDLE_TIMEOUT` - Time in milliseconds an idle connection is kept in the connection pool (default: 90000)
+- `OPERATON_TASK_WORKER_POOL_MAX_IDLE_PER_HOST` - Maximum number of idle connections in the connection pool (default: 10)
+- `OPERATON_TASK_WORKER_TLS_CA_BUNDLE` - PEM file with additional CA certificates to trust
+- `OPERATON_TASK_WORKER_TLS_CLIENT_CERT` - Client certificate for mutual TLS, as PEM or PKCS#12 (`.p12`/`.pfx`)
+- `OPERATON_TASK_WORKER_TLS_CLIENT_KEY` - PEM private key of the client certificate
+- `OPERATON_TASK_WORKER_TLS_CLIENT_CERT_PASSWORD` - Password of a PKCS#12 client certificate
+- `OPERATON_TASK_WORKER_TLS_INSECURE_SKIP_VERIFY` - Accept invalid server certificates, for local development only (default: false)
 - `RUST_LOG` - Logging level for the application, e.g. `info,operaton_task_worker=debug`
 
 ```ignore
@@ -78,9 +124,98 @@ let config = ConfigParams::default()
     .with_auth("user".to_string(), "pass".to_string())
     .with_poll_interval(1000)
     .with_worker_id("operaton_task_worker".to_string())
-    .with_lock_duration(60_000);
+    .with_lock_duration(60_000)
+    .with_max_tasks(10)
+    .with_use_priority(true)
+    .with_async_response_timeout(30_000);
+```
+
+### Sharing the REST client
+
+All calls to Operaton go through an `OperatonClient`, which owns one HTTP client and therefore one connection pool.
+Create the client yourself and pass a clone to `poll_with_client` to use the same client in your own code.
+All client calls return an `OperatonError`, which distinguishes transport errors, timeouts, deserialization errors and
+engine errors. Engine errors carry the HTTP status and the parsed Operaton error body (`type`, `message`, `code`):
+
+```ignore
+use operaton_task_worker::{poll_with_client, settings, OperatonClient};
+
+let config = settings::load_config_from_env("OPERATON_TASK_WORKER");
+let client = OperatonClient::new(&config).expect("failed to create the Operaton REST client");
+poll_with_client(config, client.clone()).await;
+```
+
+Requests which are safe to repeat (fetchAndLock, extendLock, unlock and complete) are retried when they fail with a
+transient error: a connection error, a timeout or the status 429, 502, 503 or 504. The client waits with capped
+exponential backoff and jitter between the retries, or as long as the engine asks for with `Retry-After`, but at most
+`http_retry_max_delay` milliseconds. With an `http_retry_max_delay` of `0`, the backoff is not limited, and
+`Retry-After` is honored for up to one minute.
+
+### Querying External Tasks
+
+For monitoring and administration, `OperatonClient::query_external_tasks` and `OperatonClient::count_external_tasks`
+query the external tasks of the engine. Build the query with `query::ExternalTaskQuery` to filter by topic, lock state,
+retries, worker, activity, process instance and priority range, and to sort and page the results:
+
+```ignore
+use operaton_task_worker::query::{ExternalTaskQuery, ExternalTaskSortBy, SortOrder};
+
+let query = ExternalTaskQuery::default()
+    .with_topic_name("invoicing")
+    .with_retries_left(false)
+    .with_sorting(ExternalTaskSortBy::CreateTime, SortOrder::Desc)
+    .with_paging(0, 50);
+let incidents = client.query_external_tasks(&query).await?;
+let total = client.count_external_tasks(&query).await?;
 ```
 
+### Authentication
+
+Requests are authenticated by an `auth::AuthProvider`. The provider is built from the configuration: OAuth2 client
+credentials (`auth::OAuth2ClientCredentials`, the access token is cached and refreshed before it expires), a static
+bearer token (`auth::BearerToken`) or HTTP Basic authentication (`auth::BasicAuth`). To use a custom authentication
+scheme, implement `AuthProvider` and pass it to `OperatonClient::with_auth_provider`.
+
+When the engine rejects a request with `401 Unauthorized`, e.g. because the access token was revoked before it
+expired, the client calls `AuthProvider::invalidate`. If the provider can refresh its credentials, like the OAuth2
+client credentials, the request is repeated once with fresh credentials; static credentials are not sent again.
+Secrets are redacted when the configuration is logged.
+
+### TLS
+
+The TLS implementation is selected with cargo features: `native-tls` (default) uses the platform's TLS library,
+`rustls-tls` uses rustls with the Mozilla root certificates:
+
+```toml
+operaton-task-worker = { version = "0.1", default-features = false, features = ["rustls-tls"] }
+```
+
+Set `tls_ca_bundle` to trust the certificates of a company CA in addition to the default roots. For mutual TLS, set
+`tls_client_cert` to a PEM certificate together with `tls_client_key`, or to a PKCS#12 archive (`.p12`/`.pfx`) together
+with `tls_client_cert_password`. PKCS#12 archives are only supported with `native-tls`.
+An unreadable or invalid certificate file makes `OperatonClient::new` fail with `OperatonError::Configuration`.
+Without either feature, the client only supports plain HTTP, and any TLS setting makes `OperatonClient::new` fail
+with `OperatonError::Configuration`.
+
+### Durable Outbox
+
+Without outbox, the result of a handler is lost if it cannot be sent to the engine, e.g. because the engine is briefly
+unreachable. The task is fetched again after its lock expired, and the handler runs again.
+With `outbox_path`, completions, BPMN errors and failure reports are appended to a local JSON Lines file before they
+are sent. Results which could not be sent are retried in the background with exponential backoff, also after a restart
+of the worker, until the engine accepts them or reports that the task does not exist anymore. If a task with a pending
+result is fetched again, the worker sends the stored result instead of executing the handler again; a result is never
+sent twice at the same time. Results which the engine rejects for good, e.g. because of an invalid variable, are not
+retried but moved to a dead letter file next to the outbox (`outbox.jsonl` becomes `outbox.dead.jsonl`).
+
+### Deploying Process Models
+
+With `deployment_dir`, the worker deploys all `.bpmn`, `.dmn` and `.form` files of the directory and its
+subdirectories through `/deployment/create` at startup, and only starts polling after the deployment succeeded.
+The engine filters duplicates by the deployment name, so restarting the worker with unchanged files does not create new
+versions of the process definitions. If the engine is temporarily unreachable, the deployment is retried; other errors,
+e.g. an invalid BPMN file, stop the worker. Resources can also be deployed with `OperatonClient::create_deployment`.
+
 ### Registering a Task Handler
 
 Create a function with the `task_handler` attribute and annotate it with the name of the task to be handled.
@@ -91,31 +226,222 @@ The function must have the following signature:
 fn any_function_name(_input: &operaton_task_worker::types::InputVariables) -> Result<operaton_task_worker::types::OutputVariables, Box<dyn std::error::Error>>
 ```
 
+#### Topics and Task Acquisition
+The worker acquires tasks via `POST /external-task/fetchAndLock`, so a task is locked in the same call that fetches it.
+It subscribes to the topics of all registered handlers. By default, the topic of a handler is its `name`.
+If the topic of the external task differs from its activityId, set it explicitly:
+
+```ignore
+#[task_handler(name = "ServiceTask_ID", topic = "my-topic", lock_duration = 120000)]
+```
+
+A fetched task is dispatched to the handler registered for its activityId, or else to the handler named after its topic.
+The optional `lock_duration` (in milliseconds) overrides the configured lock duration for the handler's topic.
+
+Tasks of topics without a handler are never locked, because the worker only subscribes to the topics of its handlers.
+If a fetched task has neither a handler for its activityId nor for its topic, the `unhandled_task_policy` applies:
+`unlock` unlocks the task right away, `failure` reports a failure according to the retry policy like a failed handler,
+so the retries decrease until the task becomes an incident, and `incident` reports a failure with no retries left, which
+creates an incident right away.
+An unlocked task can be fetched again by the same worker, so `unlock` is meant for topics which are also served by
+another worker. To avoid fetching and unlocking the same tasks in a tight loop, the worker waits before the next fetch
+after it unlocked tasks, starting with `poll_interval` and doubling up to one minute as long as it keeps unlocking tasks.
+
+From the moment a batch of tasks is fetched, the worker extends the lock of every task of the batch every half lock
+duration via `/external-task/{id}/extendLock`, so neither long-running handlers nor tasks waiting for the handlers before
+them in the batch lose their lock. The heartbeat of a task stops when its handler finishes; a lock extension which is
+still running, e.g. retrying against a flaky engine, is abandoned, so the result is reported right away.
+Handlers are executed on Tokio's blocking thread pool.
+
+With a non-zero `async_response_timeout`, the worker uses long polling: the engine holds the fetch request open until
+tasks are available or the timeout elapses, and the worker only waits `poll_interval` milliseconds after a failed request.
+
 #### Input Variables
 The input variables are a `HashMap` of `String` to `structures::ProcessInstanceVariable`.
 The values are deserialized and are statically typed according to the type of the variable.
+All types of the engine are supported: `String`, `Boolean`, `Integer`, `Short`, `Long`, `Double`, `Date`, `Null`,
+`Json`, `Xml`, `Object`, `File` and `Bytes`. The accessors `as_str`, `as_bool`, `as_i32`, `as_i64`, `as_f64`, `as_date`,
+`as_json`, `as_xml`, `as_object`, `as_file`, `as_bytes` and `is_null` return `None` (or `false`) for variables of
+another type; the integer accessors also accept the smaller integer types. Use `types::out_date`, `types::out_short`
+and `types::out_null` to return the corresponding types.
+
+`as_json` returns the JSON document of a `Json` variable and of an `Object` variable serialized as `application/json`,
+whether the engine sends it as serialized string or as deserialized value. The worker fetches the tasks with
+`deserializeValues=false`, so the engine sends the serialized documents. If the engine still only sends the metadata of
+a JSON node instead of the document, the worker fetches the document of that variable before the handler runs.
+
+Variables which cannot be parsed, e.g. because of an unknown type or a value which does not match its type, are logged
+with a warning and skipped, so the handler runs without them. With `strict_variables`, the handler is not executed
+instead, and the task fails with an incident whose details list every variable that could not be parsed and why.
+`structures::process_variables::try_parse_process_instance_variables` and `ServiceTask::try_variables` return these
+diagnostics as error.
+
+The variables are fetched together with the task and contain the variables visible at the task's execution.
+To fetch only specific variables or only the local variables of the task's execution, use the `variables` and
+`local_variables` arguments:
+
+```ignore
+#[task_handler(name = "ServiceTask_ID", variables = ["orderId", "amount"], local_variables = true)]
+```
+
+#### Typed Input Variables
+Instead of reading the variables one by one, derive `variables::FromVariables` for a struct. Each field is read from
+the variable of the same name; `rename` picks another variable, `default` uses `Default::default()` (or the given
+function) if the variable is missing or `null`, `Option` fields are `None` in that case, and `json` deserializes a
+Json variable with serde:
+
+```ignore
+use operaton_task_worker::variables::FromVariables;
+
+#[derive(FromVariables)]
+struct InvoiceInput {
+    #[variable(rename = "invoiceId")]
+    invoice_id: String,
+    amount: f64,
+    #[variable(default)]
+    reminders: i32,
+    approver: Option<String>,
+    #[variable(json)]
+    items: Vec<Item>,
+}
+
+let input = InvoiceInput::from_variables(input)?;
+```
+
+If variables are missing or have the wrong type, `from_variables` returns a `variables::VariablesError` which lists
+every offending variable; returned from the handler with `?`, it is reported as failure of the task.
+
+Date variables are read into chrono's `DateTime<FixedOffset>` or `DateTime<Utc>`. To use `time::OffsetDateTime`
+instead, enable the `time` feature:
+
+```toml
+operaton-task-worker = { version = "0.1", features = ["time"] }
+```
+
+#### Files and Binary Data
+File and Bytes variables are `ProcessInstanceVariable::File` and `ProcessInstanceVariable::Bytes`. A File variable
+carries the filename, mime type and encoding, but not its content, which is downloaded from
+`/variable-instance/{id}/data` when the handler asks for it with `TaskContext::variable_data`. To return files or
+binary data, use `types::out_file` and `types::out_bytes`, which Base64 encode the content:
+
+```ignore
+let pdf = context.variable_data("invoice")?;
+let mut output = OutputVariables::new();
+output.insert("stampedInvoice".to_string(), out_file("invoice.pdf", Some("application/pdf"), &stamp(&pdf)));
+```
+
+#### Filtering by Process
+A handler can be restricted to the tasks of specific process definitions, a version tag, a business key or process
+variable values. This way, one worker serves only the processes it was deployed for, even if other processes use the
+same activity ids or topics:
+
+```ignore
+#[task_handler(name = "ServiceTask_ID", process_definition_keys = ["Invoice"], process_definition_version_tag = "2.0", process_variables(region = "EU"))]
+```
+
+The filters are sent with the subscription of the handler's topic. If several handlers share a topic, their process
+definition keys are joined and the other filters only apply if all handlers of the topic set them to the same value.
+A fetched task is dispatched to a handler whose process definition keys, version tag and business key match the task.
+The filter of a topic subscription can also be configured with `ConfigParams::with_subscription_filter` or the
+`subscription_filters` setting, which replaces the filters of the topic's handlers.
+
+#### Tenants
+On a multi-tenant engine, restrict the worker to the tasks of its tenants with `tenant_id_in` (or `without_tenant_id`
+for tasks without a tenant); the filter applies to all topic subscriptions. A handler can take the
+`context::TaskContext` of its task as second argument, which exposes the tenant id, e.g. to pick tenant-specific credentials:
+
+```ignore
+#[task_handler(name = "ServiceTask_ID")]
+fn any_function_name(input: &InputVariables, context: &TaskContext) -> Result<OutputVariables, Box<dyn std::error::Error>>
+```
+
+Handlers without the context argument keep working. A `registry::Handler` submitted by hand with `inventory::submit!`
+wraps its function in `types::HandlerFn::Plain` (`types::ExternalTaskFn`, without context) or
+`types::HandlerFn::WithContext` (`types::ContextTaskFn`).
+
+#### Interacting with the Engine from a Handler
+Through the `TaskContext`, a handler can correlate messages, send signals and start process instances. The calls use
+the worker's REST client with its authentication and block until the engine answered:
+
+```ignore
+use operaton_task_worker::engine::{MessageCorrelation, ProcessStart, Signal};
+
+context.correlate_message(&MessageCorrelation::new("PaymentReceived").with_business_key("order-4711"))?;
+context.send_signal(&Signal::new("StockChanged"))?;
+let instance = context.start_process_instance(&ProcessStart::new("Invoice").with_variable("orderId", out_string("4711")))?;
+```
+
+The same calls are available as async functions of `OperatonClient`.
 
 #### Returning Successful Executions
 - Return `Ok(HashMap::new())` to indicate that the task was executed successfully.
 - Return `Ok(...)` with a non-empty output variable map to indicate that the task was executed successfully and that the output variables should be updated.
 
+#### Typed Output Variables
+Derive `variables::IntoVariables` to return a struct instead of building the map by hand. The variable types follow
+the field types (`i16` is Short, `i32` Integer, `i64` Long, `f64` Double, chrono's `DateTime` Date, and
+`time::OffsetDateTime` Date with the `time` feature); `json` serializes
+a field with serde into a Json variable, `skip_none` leaves out `None` instead of setting the variable to `null`,
+`transient` sets a transient variable and `local` sets the variable in the scope of the task's execution:
+
+```ignore
+use operaton_task_worker::variables::IntoVariables;
+
+#[derive(IntoVariables)]
+struct InvoiceResult {
+    #[variable(rename = "invoiceNumber")]
+    invoice_number: String,
+    total: f64,
+    #[variable(skip_none)]
+    discount: Option<f64>,
+    #[variable(local)]
+    reviewer: String,
+}
+
+Ok(result.into_variables()?)
+```
+
+Single variables can be marked with `OutVariable::with_transient` and `OutVariable::with_local` as well.
+
 #### Returning errors from a handler
 - For a BPMN Business Error (Camunda 7/Operaton), return `Err(Box::new(BpmnError::new(code, message)))`.
   The worker will call `/external-task/{id}/bpmnError`.
-- For technical failures, return any other error; the worker calls `/external-task/{id}/failure` with `retries=0`.
+- For technical failures, return any other error; the worker calls `/external-task/{id}/failure`.
+
+The retries of a failed task are handled by the engine according to the retry policy: the first failure sets the
+retries of the task to `max_retries`, every further failure decrements them and waits according to the backoff
+(`fixed`, `linear` or `exponential`) before the task is fetched again. The failure which leaves no retries creates an incident.
+By default, `max_retries` is `0`, so every failure creates an incident right away. Handlers can override the policy:
+
+```ignore
+#[task_handler(name = "ServiceTask_ID", max_retries = 3, retry_backoff = "exponential", retry_delay = 1000, retry_max_delay = 60000)]
+```
 
 
 **/
 
 mod polling;
+mod heartbeat;
+mod outbox;
+mod tls;
 pub mod structures;
 pub mod types;
-mod api;
+pub mod api;
+pub mod context;
+pub mod deployment;
+pub mod engine;
+pub mod auth;
+pub mod error;
+pub mod query;
 pub mod registry;
+pub mod retry;
 pub mod settings;
+pub mod variables;
 
 pub use inventory;
 pub use operaton_task_worker_macros::task_handler;
+pub use api::OperatonClient;
+pub use error::OperatonError;
 
 use crate::settings::ConfigParams;
 
@@ -124,6 +450,13 @@ pub async fn poll(config: ConfigParams) {
     polling::start_polling_loop(config).await;
 }
 
+/// Start the polling loop asynchronously with an existing [OperatonClient]. Call this inside a Tokio runtime.
+///
+/// Use this function to share the client (and its connection pool) between the polling loop and your own code.
+pub async fn poll_with_client(config: ConfigParams, client: OperatonClient) {
+    polling::start_polling_loop_with_client(config, client).await;
+}
+
 /// Convenience: start the polling loop and block the current thread until it ends (infinite loop).
 pub fn poll_blocking(config: ConfigParams) {
     let rt = tokio::runtime::Runtime::new().expect("failed to create Tokio runtime");
@@ -0,0 +1,526 @@
+//! This module includes the durable outbox for the results of task executions.
+//!
+//! Before a result is sent to the engine, it is appended to a JSON Lines file. Once the engine accepts the result or
+//! reports that the task does not exist (anymore), an acknowledgement is appended. Results without acknowledgement
+//! are loaded again after a restart and retried with backoff, so the result of a handler is not lost while the
+//! engine is unreachable. Results which the engine rejects for good are moved to a dead letter file next to the outbox.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::api::OperatonClient;
+use crate::error::OperatonError;
+use crate::retry::RetryPolicy;
+use crate::types::OutputVariables;
+
+/// The result of a task execution which is reported to the engine
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum TaskResult {
+    Complete {
+        variables: OutputVariables,
+    },
+    #[serde(rename_all = "camelCase")]
+    BpmnError {
+        error_code: String,
+        error_message: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failure {
+        error_message: String,
+        error_details: Option<String>,
+        retries: i32,
+        retry_timeout: i64,
+    },
+}
+
+impl TaskResult {
+    fn describe(&self) -> &'static str {
+        match self {
+            TaskResult::Complete { .. } => "completion",
+            TaskResult::BpmnError { .. } => "BPMN error",
+            TaskResult::Failure { .. } => "failure report",
+        }
+    }
+
+    async fn send(&self, client: &OperatonClient, task_id: &str) -> Result<(), OperatonError> {
+        match self {
+            TaskResult::Complete { variables } => client.complete_external_task(task_id, variables.clone()).await,
+            TaskResult::BpmnError { error_code, error_message } => {
+                client.report_bpmn_error(task_id, error_code, error_message.as_deref(), None).await
+            }
+            TaskResult::Failure { error_message, error_details, retries, retry_timeout } => {
+                client
+                    .report_external_task_failure(task_id, error_message, error_details.as_deref(), *retries, *retry_timeout)
+                    .await
+            }
+        }
+    }
+}
+
+/// How sending a task result to the engine ended
+pub(crate) enum Delivery {
+    /// The engine accepted the result, or the task does not exist anymore or is locked by another worker,
+    /// so the result cannot be sent at all
+    Done,
+    /// Sending failed for a reason that may go away, e.g. the engine is unreachable
+    Retry,
+    /// The engine rejected the result for good, e.g. because of an invalid variable
+    Rejected(OperatonError),
+}
+
+/// Send a task result to the engine
+pub(crate) async fn send_result(client: &OperatonClient, task_id: &str, result: &TaskResult) -> Delivery {
+    match result.send(client, task_id).await {
+        Ok(()) => {
+            info!("Sent the {} of external task {}", result.describe(), task_id);
+            Delivery::Done
+        }
+        Err(err) if err.is_not_found() || err.is_locked_by_other_worker() => {
+            warn!("The {} of external task {} was rejected because the lock expired: {}", result.describe(), task_id, err);
+            Delivery::Done
+        }
+        Err(err) if err.is_transient() => {
+            error!("Could not send the {} of external task {}: {}", result.describe(), task_id, err);
+            Delivery::Retry
+        }
+        Err(err) => {
+            error!("The engine rejected the {} of external task {}, it will not be sent again: {}", result.describe(), task_id, err);
+            Delivery::Rejected(err)
+        }
+    }
+}
+
+/// A line of the outbox file
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum Record {
+    #[serde(rename_all = "camelCase")]
+    Add { id: u64, task_id: String, result: TaskResult },
+    Ack { id: u64 },
+}
+
+/// A line of the dead letter file
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeadLetter<'a> {
+    task_id: &'a str,
+    result: &'a TaskResult,
+    error: String,
+}
+
+struct PendingResult {
+    task_id: String,
+    result: TaskResult,
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+struct OutboxState {
+    file: File,
+    next_id: u64,
+    pending: BTreeMap<u64, PendingResult>,
+    /// The ids of the results which are being sent right now
+    delivering: HashSet<u64>,
+}
+
+/// Marks a result as being sent until it is dropped, so the polling loop and the retry loop do not send it in parallel
+struct DeliveryClaim<'a> {
+    outbox: &'a Outbox,
+    id: u64,
+}
+
+impl Drop for DeliveryClaim<'_> {
+    fn drop(&mut self) {
+        self.outbox.state.lock().unwrap().delivering.remove(&self.id);
+    }
+}
+
+/// The append-only outbox file with the results which were not acknowledged by the engine yet
+pub(crate) struct Outbox {
+    path: PathBuf,
+    retry_policy: RetryPolicy,
+    state: Mutex<OutboxState>,
+}
+
+impl Outbox {
+    /// Open the outbox file, load the pending results and compact the file to the pending results
+    pub(crate) fn open(path: &Path, retry_policy: RetryPolicy) -> io::Result<Self> {
+        let mut pending = BTreeMap::new();
+        let mut next_id = 0;
+
+        if path.exists() {
+            for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Record>(&line) {
+                    Ok(Record::Add { id, task_id, result }) => {
+                        next_id = next_id.max(id + 1);
+                        pending.insert(id, PendingResult { task_id, result, attempts: 0, next_attempt: Instant::now() });
+                    }
+                    Ok(Record::Ack { id }) => {
+                        pending.remove(&id);
+                    }
+                    // A crash while appending leaves an incomplete last line behind
+                    Err(err) => warn!("Skip invalid line {} of the outbox '{}': {}", index + 1, path.display(), err),
+                }
+            }
+        }
+
+        let file = Self::rewrite(path, &pending)?;
+        if !pending.is_empty() {
+            info!("Loaded {} unsent task results from the outbox '{}'", pending.len(), path.display());
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            retry_policy,
+            state: Mutex::new(OutboxState { file, next_id, pending, delivering: HashSet::new() }),
+        })
+    }
+
+    /// Write the pending results to a new file, which replaces the outbox file
+    fn rewrite(path: &Path, pending: &BTreeMap<u64, PendingResult>) -> io::Result<File> {
+        let tmp_path = path.with_extension("tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        for (id, entry) in pending {
+            let record = Record::Add { id: *id, task_id: entry.task_id.clone(), result: entry.result.clone() };
+            writeln!(tmp, "{}", serde_json::to_string(&record)?)?;
+        }
+        tmp.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+
+        OpenOptions::new().append(true).open(path)
+    }
+
+    fn append(file: &mut File, record: &impl Serialize) -> io::Result<()> {
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        file.sync_data()
+    }
+
+    /// Durably store a result before it is sent and return its outbox id.
+    /// The caller sends the result right away, so the retry loop only picks it up after the first retry delay.
+    pub(crate) fn push(&self, task_id: &str, result: &TaskResult) -> io::Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        Self::append(&mut state.file, &Record::Add { id, task_id: task_id.to_string(), result: result.clone() })?;
+
+        state.next_id += 1;
+        state.pending.insert(
+            id,
+            PendingResult {
+                task_id: task_id.to_string(),
+                result: result.clone(),
+                attempts: 0,
+                next_attempt: self.next_attempt(0),
+            },
+        );
+        Ok(id)
+    }
+
+    /// The time of the next attempt to send a result, which is postponed while the result is being sent
+    fn next_attempt(&self, attempts: u32) -> Instant {
+        Instant::now() + Duration::from_millis(self.retry_policy.delay_for_retry(attempts + 1))
+    }
+
+    /// Remove a result which was accepted by the engine. The file is truncated once no results are pending.
+    pub(crate) fn ack(&self, id: u64) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.remove(&id).is_none() {
+            return Ok(());
+        }
+
+        if state.pending.is_empty() {
+            state.file.set_len(0)?;
+            state.file.sync_data()
+        } else {
+            Self::append(&mut state.file, &Record::Ack { id })
+        }
+    }
+
+    /// Schedule the next attempt to send a result after a failed attempt
+    fn reschedule(&self, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(entry) = state.pending.get_mut(&id) {
+            entry.attempts += 1;
+            entry.next_attempt = self.next_attempt(entry.attempts);
+        }
+    }
+
+    /// Take the pending result of a task for sending, if any
+    pub(crate) fn pending_for_task(&self, task_id: &str) -> Option<(u64, TaskResult)> {
+        let mut state = self.state.lock().unwrap();
+        let (id, entry) = state.pending.iter_mut().find(|(_, entry)| entry.task_id == task_id)?;
+        entry.next_attempt = self.next_attempt(entry.attempts);
+        Some((*id, entry.result.clone()))
+    }
+
+    /// Take the pending results which are due for sending and are not being sent already
+    fn due(&self) -> Vec<(u64, String, TaskResult)> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let OutboxState { pending, delivering, .. } = &mut *state;
+        pending
+            .iter_mut()
+            .filter(|(id, entry)| entry.next_attempt <= now && !delivering.contains(id))
+            .map(|(id, entry)| {
+                entry.next_attempt = self.next_attempt(entry.attempts);
+                (*id, entry.task_id.clone(), entry.result.clone())
+            })
+            .collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    /// Mark a pending result as being sent. Returns `None` if it is being sent already or was acknowledged meanwhile.
+    fn claim(&self, id: u64) -> Option<DeliveryClaim<'_>> {
+        let mut state = self.state.lock().unwrap();
+        if !state.pending.contains_key(&id) || !state.delivering.insert(id) {
+            return None;
+        }
+        Some(DeliveryClaim { outbox: self, id })
+    }
+
+    /// The file of the results which the engine rejected for good, next to the outbox file
+    fn dead_letter_path(&self) -> PathBuf {
+        self.path.with_extension("dead.jsonl")
+    }
+
+    /// Keep a result which the engine rejected for good, so it can be inspected
+    fn dead_letter(&self, task_id: &str, result: &TaskResult, err: &OperatonError) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(self.dead_letter_path())?;
+        Self::append(&mut file, &DeadLetter { task_id, result, error: err.to_string() })
+    }
+
+    /// Send a stored result and acknowledge it once it is done, otherwise schedule a retry.
+    /// A result is only sent by one caller at a time.
+    pub(crate) async fn deliver(&self, client: &OperatonClient, id: u64, task_id: &str, result: &TaskResult) {
+        let Some(_claim) = self.claim(id) else {
+            debug!("The {} of task {} is already being sent", result.describe(), task_id);
+            return;
+        };
+
+        match send_result(client, task_id, result).await {
+            Delivery::Done => {}
+            Delivery::Retry => {
+                self.reschedule(id);
+                warn!("The {} of task {} is kept in the outbox and will be retried", result.describe(), task_id);
+                return;
+            }
+            Delivery::Rejected(err) => match self.dead_letter(task_id, result, &err) {
+                Ok(()) => warn!(
+                    "Moved the {} of task {} to the dead letter file '{}'",
+                    result.describe(),
+                    task_id,
+                    self.dead_letter_path().display()
+                ),
+                Err(err) => error!("Could not write the {} of task {} to the dead letter file: {}", result.describe(), task_id, err),
+            },
+        }
+
+        if let Err(err) = self.ack(id) {
+            error!("Could not acknowledge the {} of task {} in the outbox '{}': {}", result.describe(), task_id, self.path.display(), err);
+        }
+    }
+}
+
+/// Retry the pending results of the outbox in the background
+pub(crate) fn start_retry_loop(client: OperatonClient, outbox: Arc<Outbox>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        debug!("Start the outbox retry loop with {} pending results", outbox.len());
+        let tick = Duration::from_millis(outbox.retry_policy.delay.clamp(100, 1_000));
+        loop {
+            for (id, task_id, result) in outbox.due() {
+                debug!("Retry the {} of task {} from the outbox", result.describe(), task_id);
+                outbox.deliver(&client, id, &task_id, &result).await;
+            }
+            tokio::time::sleep(tick).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::out_string;
+
+    fn outbox_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("operaton-task-worker-outbox-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn complete() -> TaskResult {
+        TaskResult::Complete { variables: OutputVariables::from([("approved".to_string(), out_string("yes"))]) }
+    }
+
+    #[test]
+    fn test_pending_results_survive_a_restart() {
+        let path = outbox_path("restart");
+
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        let completed = outbox.push("task-1", &complete()).unwrap();
+        outbox.push("task-2", &TaskResult::BpmnError { error_code: "E1".to_string(), error_message: None }).unwrap();
+        outbox.ack(completed).unwrap();
+        drop(outbox);
+
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        assert_eq!(outbox.len(), 1);
+        assert!(outbox.pending_for_task("task-1").is_none());
+        assert!(matches!(outbox.pending_for_task("task-2"), Some((_, TaskResult::BpmnError { .. }))));
+
+        // New results do not reuse the ids of loaded results
+        let id = outbox.push("task-3", &complete()).unwrap();
+        assert_eq!(outbox.len(), 2);
+        assert!(id > outbox.pending_for_task("task-2").unwrap().0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_is_truncated_when_all_results_are_acknowledged() {
+        let path = outbox_path("truncate");
+
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        let id = outbox.push("task-1", &complete()).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+
+        outbox.ack(id).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_incomplete_last_line_is_skipped() {
+        let path = outbox_path("incomplete");
+
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        outbox.push("task-1", &complete()).unwrap();
+        drop(outbox);
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"op\":\"add\",\"id\":1,").unwrap();
+
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        assert_eq!(outbox.len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_result_is_kept_until_the_engine_accepts_it() {
+        use crate::settings::ConfigParams;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/complete"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/complete"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        // Without HTTP retries, so the outbox has to retry the result
+        let config = ConfigParams::default()
+            .with_url(url::Url::parse(&server.uri()).unwrap())
+            .with_http_retry(0, 0, 0);
+        let client = OperatonClient::new(&config).unwrap();
+
+        let path = outbox_path("deliver");
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        let id = outbox.push("task-1", &complete()).unwrap();
+
+        outbox.deliver(&client, id, "task-1", &complete()).await;
+        assert_eq!(outbox.len(), 1);
+
+        let (id, task_id, result) = outbox.due().pop().unwrap();
+        outbox.deliver(&client, id, &task_id, &result).await;
+        assert_eq!(outbox.len(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn client(server: &wiremock::MockServer) -> OperatonClient {
+        let config = crate::settings::ConfigParams::default()
+            .with_url(url::Url::parse(&server.uri()).unwrap())
+            .with_http_retry(0, 0, 0);
+        OperatonClient::new(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejected_result_is_moved_to_the_dead_letter_file() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/complete"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "type": "InvalidRequestException",
+                "message": "Cannot complete external task: invalid variable type"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let path = outbox_path("rejected");
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        let id = outbox.push("task-1", &complete()).unwrap();
+
+        outbox.deliver(&client(&server), id, "task-1", &complete()).await;
+        assert_eq!(outbox.len(), 0);
+
+        let dead_letters = std::fs::read_to_string(outbox.dead_letter_path()).unwrap();
+        let dead_letter: serde_json::Value = serde_json::from_str(dead_letters.lines().next().unwrap()).unwrap();
+        assert_eq!(dead_letter["taskId"], "task-1");
+        assert_eq!(dead_letter["result"]["type"], "complete");
+
+        std::fs::remove_file(outbox.dead_letter_path()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_result_is_not_sent_twice_in_parallel() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/complete"))
+            .respond_with(ResponseTemplate::new(204).set_delay(Duration::from_millis(100)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let path = outbox_path("parallel");
+        let outbox = Outbox::open(&path, RetryPolicy::default()).unwrap();
+        let id = outbox.push("task-1", &complete()).unwrap();
+
+        // The polling loop and the retry loop pick up the same result
+        let client = client(&server);
+        let result = complete();
+        tokio::join!(outbox.deliver(&client, id, "task-1", &result), outbox.deliver(&client, id, "task-1", &result));
+        assert_eq!(outbox.len(), 0);
+
+        // A result which was acknowledged meanwhile is not sent again
+        outbox.deliver(&client, id, "task-1", &result).await;
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
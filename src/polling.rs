@@ -2,90 +2,559 @@
 
 use std::collections::HashMap;
 use log::{debug, error, info, trace, warn};
-use crate::{api, registry};
+use crate::registry;
+use crate::api::{FetchAndLock, OperatonClient, TopicSubscription};
+use crate::context::TaskContext;
+use crate::deployment::Deployment;
+use crate::error::OperatonError;
 use crate::structures::process_variables::ProcessInstanceVariable;
-use crate::settings::ConfigParams;
-use crate::types::BpmnError;
+use crate::structures::service_task::ServiceTask;
+use crate::settings::{ConfigParams, StartupCheck, UnhandledTaskPolicy};
+use crate::heartbeat::LockHeartbeat;
+use crate::outbox::{self, Outbox, TaskResult};
+use crate::retry::{Backoff, RetryPolicy};
+use crate::types::{BpmnError, OutputVariables};
+use std::sync::Arc;
 
 pub async fn start_polling_loop(config: ConfigParams) {
+    let client = OperatonClient::new(&config).expect("failed to create the Operaton REST client");
+    start_polling_loop_with_client(config, client).await;
+}
+
+pub async fn start_polling_loop_with_client(config: ConfigParams, client: OperatonClient) {
 
     env_logger::init();
 
     info!("Load Operaton Task Worker with configuration: {:#?}", config);
 
-    if config.username().is_empty() || config.password().is_empty() {
+    if !config.has_authentication() {
         warn!("No authentication set up. Operaton should be protected by authentication in productive use.");
     }
 
+    check_engine(&client, &config).await;
+
+    deploy_resources(&client, &config).await;
+
+    let topics = topic_subscriptions(&config);
+    if topics.is_empty() {
+        warn!("No task handlers registered. The worker will not fetch any tasks.");
+    } else {
+        info!("Subscribing to topics: {:?}", topics.iter().map(|t| t.topic_name.as_str()).collect::<Vec<_>>());
+    }
+
+    let fetch = FetchAndLock::from_config(&config, topics);
+
+    // Results are stored in the outbox before they are sent and retried in the background until the engine accepts them
+    let outbox = config.outbox_path().map(|path| {
+        let outbox = Arc::new(Outbox::open(path, config.outbox_retry_policy()).expect("failed to open the outbox"));
+        outbox::start_retry_loop(client.clone(), outbox.clone());
+        outbox
+    });
+
     trace!("Enter the main loop");
 
+    // The number of consecutive batches in which tasks without a handler were unlocked
+    let mut unlocking_batches: u32 = 0;
+
     loop {
-        match api::get_open_service_tasks(&config).await {
+        // With long polling, the engine holds the request open until tasks arrive, so the worker
+        // only waits between requests when long polling is disabled or the request failed.
+        let mut wait_before_next_fetch = !config.long_polling();
+        let mut wait_duration = config.poll_interval() as u64;
+
+        match client.fetch_and_lock(&fetch).await {
             Ok(service_tasks) => {
                 info!(
-                    "We received {} open external Service Tasks from Operaton.",
+                    "We fetched and locked {} external Service Tasks from Operaton.",
                     service_tasks.len()
                 );
 
-                for service_task in service_tasks {
-                    // Try to lock the specific external task and read its input variables
-                    if let Err(err) = api::lock_external_task(&config, service_task.id(), config.lock_duration()).await {
-                        warn!("Could not lock task {}: {:#?}", service_task.id(), err);
-                        continue;
-                    }
-
-                    let input_vars: HashMap<String, ProcessInstanceVariable> = api::get_process_instance_variables(&config, service_task.process_instance_id()).await.unwrap_or_else(|err| {
-                        error!("Error while fetching external task variables: {:#?}", err);
-                        HashMap::new()
-                    });
-                    trace!("External task variables for {} => {:#?}", service_task.id(), input_vars);
-
-                    if let Some(function) = registry::find(service_task.activity_id()) {
-                        debug!("Executing function for Service Task: {:#?}", service_task);
-                        match function(&input_vars) {
-                            Ok(output_vars) => {
-                                if let Err(err) = api::complete_external_task(&config, service_task.id(), output_vars).await {
-                                    error!("Could not complete external task {}: {:#?}", service_task.id(), err);
-                                } else {
-                                    info!("Completed external task {}", service_task.id());
-                                }
-                            }
-                            Err(err) => {
-                                error!("Execution of function for Service Task {} failed: {:#?}", service_task.id(), err);
-                                // Distinguish BPMN business errors from technical failures
-                                if let Some(bpmn) = err.downcast_ref::<BpmnError>() {
-                                    if let Err(e) = api::report_bpmn_error(
-                                        &config,
-                                        service_task.id(),
-                                        &bpmn.code,
-                                        bpmn.message.as_deref(),
-                                        None,
-                                    ).await {
-                                        error!("Could not report BPMN error for task {}: {:#?}", service_task.id(), e);
-                                    }
-                                } else {
-                                    if let Err(e) = api::report_external_task_failure(
-                                        &config,
-                                        service_task.id(),
-                                        &err.to_string(),
-                                        None,
-                                        0,
-                                        0,
-                                    ).await {
-                                        error!("Could not report failure for task {}: {:#?}", service_task.id(), e);
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        warn!("No function found for Service Task: {:#?}. SKIP.", service_task.activity_id());
-                    }
-                };
+                let mut unlocked = false;
+                for (service_task, heartbeat) in start_heartbeats(&client, &config, service_tasks) {
+                    unlocked |= handle_service_task(&client, &config, outbox.as_deref(), service_task, heartbeat).await;
+                }
+
+                // Unlocked tasks are fetched again right away, unless another worker picks them up first
+                if unlocked {
+                    unlocking_batches += 1;
+                    wait_before_next_fetch = true;
+                    wait_duration = unlock_backoff(&config, unlocking_batches);
+                    debug!("Unlocked tasks without a handler, wait {} ms before the next fetch", wait_duration);
+                } else {
+                    unlocking_batches = 0;
+                }
             },
-            Err(error) => error!("We were unable to receive and parse any Service Tasks. Error: {:#}", error)
+            Err(error) => {
+                error!("We were unable to receive and parse any Service Tasks. Error: {:#}", error);
+                wait_before_next_fetch = true;
+            }
         }
 
         // Wait for the in `config.poll_interval` milliseconds
-        tokio::time::sleep(tokio::time::Duration::from_millis(config.poll_interval() as u64)).await;
+        if wait_before_next_fetch {
+            tokio::time::sleep(tokio::time::Duration::from_millis(wait_duration)).await;
+        }
+    }
+}
+
+/// Check that the engine is reachable, accepts the credentials and runs a supported version
+async fn check_engine(client: &OperatonClient, config: &ConfigParams) {
+    if config.startup_check() == StartupCheck::Off {
+        return;
+    }
+
+    let problem = match client.engine_version().await {
+        Ok(version) if version.is_supported() => {
+            info!("Connected to {}", version);
+            return;
+        }
+        Ok(version) => format!("The engine version {} is not supported", version),
+        Err(err @ OperatonError::Authentication { .. }) => {
+            format!("The token endpoint rejected the client credentials: {}", err)
+        }
+        Err(err) if matches!(&err, OperatonError::Engine { status, .. } if matches!(status.as_u16(), 401 | 403)) => {
+            format!("The engine rejected the credentials: {}", err)
+        }
+        Err(err) => format!("The engine is not reachable at {}: {}", client.base_url(), err),
+    };
+
+    match config.startup_check() {
+        StartupCheck::Fail => panic!("Engine check failed. {problem}"),
+        _ => warn!("Engine check failed. {problem}"),
+    }
+}
+
+/// Deploy the resources of the deployment directory. Polling only starts after the deployment succeeded, so
+/// transient errors are retried and all other errors stop the worker.
+async fn deploy_resources(client: &OperatonClient, config: &ConfigParams) {
+    let Some(deployment_dir) = config.deployment_dir() else {
+        return;
+    };
+
+    let deployment = Deployment::from_directory(config.deployment_name(), deployment_dir)
+        .unwrap_or_else(|err| panic!("failed to read the deployment directory {}: {}", deployment_dir.display(), err))
+        .with_source(config.deployment_source())
+        .with_duplicate_filtering(true, config.deploy_changed_only());
+    if deployment.resource_names().is_empty() {
+        warn!("No resources to deploy found in {}", deployment_dir.display());
+        return;
+    }
+
+    loop {
+        match client.create_deployment(&deployment).await {
+            Ok(result) => {
+                info!("Deployed {:?} as deployment {}", deployment.resource_names(), result.id);
+                return;
+            }
+            Err(err) if err.is_transient() => {
+                warn!("Failed to deploy {}, retrying: {}", deployment.name(), err);
+                tokio::time::sleep(tokio::time::Duration::from_millis(config.poll_interval() as u64)).await;
+            }
+            Err(err) => panic!("Failed to deploy {}: {}", deployment.name(), err),
+        }
+    }
+}
+
+/// The result of a handler execution, converted so it can be sent back from the blocking thread pool
+enum HandlerOutcome {
+    Completed(OutputVariables),
+    BpmnError(BpmnError),
+    Failed(String),
+}
+
+/// Start the heartbeats of a fetched batch of tasks.
+///
+/// The tasks of a batch are handled one after another, so the locks of all of them are kept alive from the start,
+/// not only while their own handler runs.
+fn start_heartbeats(
+    client: &OperatonClient,
+    config: &ConfigParams,
+    service_tasks: Vec<ServiceTask>,
+) -> Vec<(ServiceTask, Option<LockHeartbeat>)> {
+    service_tasks
+        .into_iter()
+        .map(|service_task| {
+            let heartbeat = config.auto_extend_lock().then(|| {
+                LockHeartbeat::start(
+                    client.clone(),
+                    service_task.id().to_string(),
+                    lock_duration_for_topic(config, service_task.topic_name()),
+                )
+            });
+            (service_task, heartbeat)
+        })
+        .collect()
+}
+
+/// The maximum delay in milliseconds before the next fetch after tasks without a handler were unlocked
+const MAX_UNLOCK_BACKOFF: u64 = 60_000;
+
+/// The delay in milliseconds before the next fetch after the n-th consecutive batch with unlocked tasks.
+///
+/// The delay doubles from `poll_interval` with every batch, so a worker which keeps fetching the same tasks without
+/// a handler does not fetch and unlock them in a tight loop.
+fn unlock_backoff(config: &ConfigParams, unlocking_batches: u32) -> u64 {
+    RetryPolicy {
+        backoff: Backoff::Exponential,
+        max_retries: 0,
+        delay: (config.poll_interval() as u64).max(1),
+        max_delay: MAX_UNLOCK_BACKOFF,
+    }
+    .delay_for_retry(unlocking_batches)
+}
+
+/// The lock duration of the tasks of a topic
+fn lock_duration_for_topic(config: &ConfigParams, topic: &str) -> u64 {
+    registry::lock_duration_for_topic(topic).unwrap_or(config.lock_duration())
+}
+
+/// Stop the heartbeat of a task before its result is reported
+async fn stop_heartbeat(heartbeat: Option<LockHeartbeat>) {
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.stop().await;
+    }
+}
+
+/// Execute the handler for a fetched and locked task and report the result to Operaton.
+///
+/// The `heartbeat` keeps the lock of the task alive since it was fetched and is stopped before the result is reported.
+/// Returns whether the task was unlocked because it has no handler.
+async fn handle_service_task(
+    client: &OperatonClient,
+    config: &ConfigParams,
+    outbox: Option<&Outbox>,
+    mut service_task: ServiceTask,
+    heartbeat: Option<LockHeartbeat>,
+) -> bool {
+    // The task was executed before, but its result did not reach the engine until the lock expired
+    if let Some(outbox) = outbox
+        && let Some((id, result)) = outbox.pending_for_task(service_task.id())
+    {
+        stop_heartbeat(heartbeat).await;
+        info!("Send the result of task {} from the outbox instead of executing it again", service_task.id());
+        outbox.deliver(client, id, service_task.id(), &result).await;
+        return false;
+    }
+
+    let Some(handler) = registry::find_handler_for_service_task(&service_task) else {
+        stop_heartbeat(heartbeat).await;
+        return handle_unhandled_task(client, config, &service_task).await;
+    };
+    let function = handler.func;
+
+    resolve_json_documents(client, &mut service_task).await;
+
+    // The task is already locked and its variables are fetched by the fetchAndLock call
+    let input_vars: HashMap<String, ProcessInstanceVariable> = if config.strict_variables() {
+        match service_task.try_variables() {
+            Ok(input_vars) => input_vars,
+            Err(err) => {
+                // Broken variables do not heal by retrying, so an incident is created right away
+                stop_heartbeat(heartbeat).await;
+                error!("Variables of Service Task {} cannot be parsed: {}", service_task.id(), err);
+                let result = TaskResult::Failure {
+                    error_message: format!("{} variables cannot be parsed", err.errors.len()),
+                    error_details: Some(err.errors.iter().map(|error| error.to_string()).collect::<Vec<_>>().join("\n")),
+                    retries: 0,
+                    retry_timeout: 0,
+                };
+                report_result(client, outbox, service_task.id(), result).await;
+                return false;
+            }
+        }
+    } else {
+        service_task.variables()
+    };
+    trace!("External task variables for {} => {:#?}", service_task.id(), input_vars);
+
+    debug!("Executing function for Service Task: {:#?}", service_task);
+    let context = TaskContext::with_client(service_task.clone(), client.clone());
+
+    // Run the handler on the blocking thread pool, so the heartbeats keep running
+    let outcome = tokio::task::spawn_blocking(move || match function.call(&input_vars, &context) {
+        Ok(output_vars) => HandlerOutcome::Completed(output_vars),
+        Err(err) => match err.downcast::<BpmnError>() {
+            Ok(bpmn) => HandlerOutcome::BpmnError(*bpmn),
+            Err(err) => HandlerOutcome::Failed(err.to_string()),
+        },
+    })
+    .await
+    .unwrap_or_else(|err| HandlerOutcome::Failed(format!("Handler terminated abnormally: {err}")));
+
+    stop_heartbeat(heartbeat).await;
+
+    let result = match outcome {
+        HandlerOutcome::Completed(output_vars) => TaskResult::Complete { variables: output_vars },
+        // Distinguish BPMN business errors from technical failures
+        HandlerOutcome::BpmnError(bpmn) => {
+            error!("Execution of function for Service Task {} failed: {}", service_task.id(), bpmn);
+            TaskResult::BpmnError { error_code: bpmn.code, error_message: bpmn.message }
+        }
+        HandlerOutcome::Failed(message) => {
+            error!("Execution of function for Service Task {} failed: {}", service_task.id(), message);
+            let retry_policy = config.retry_policy().with_override(&handler.retry_policy);
+            let (retries, retry_timeout) = retry_policy.next_failure(service_task.retries());
+            if retries == 0 {
+                warn!("No retries left for task {}, an incident will be created", service_task.id());
+            } else {
+                info!("Task {} will be retried in {} ms ({} retries left)", service_task.id(), retry_timeout, retries);
+            }
+            TaskResult::Failure {
+                error_message: message,
+                error_details: None,
+                retries,
+                retry_timeout: retry_timeout as i64,
+            }
+        }
+    };
+
+    report_result(client, outbox, service_task.id(), result).await;
+    false
+}
+
+/// Send the result of a task to the engine, through the outbox if it is enabled
+async fn report_result(client: &OperatonClient, outbox: Option<&Outbox>, task_id: &str, result: TaskResult) {
+    if let Some(outbox) = outbox {
+        match outbox.push(task_id, &result) {
+            Ok(id) => {
+                outbox.deliver(client, id, task_id, &result).await;
+                return;
+            }
+            Err(err) => error!("Could not store the result of task {} in the outbox: {}", task_id, err),
+        }
+    }
+
+    outbox::send_result(client, task_id, &result).await;
+}
+
+/// Fetch the documents of Json and Object variables of which the engine sent only the metadata of their JSON node.
+/// A variable whose document cannot be fetched keeps its metadata and is reported when the variables are parsed.
+async fn resolve_json_documents(client: &OperatonClient, service_task: &mut ServiceTask) {
+    for name in service_task.variables_without_json_document() {
+        debug!("Fetch the JSON document of variable {} of Service Task {}", name, service_task.id());
+        match client.serialized_variable_value(service_task.process_instance_id(), service_task.activity_instance_id(), &name).await {
+            Ok(Some(value)) => service_task.set_variable_value(&name, value),
+            Ok(None) => warn!("Variable {} of Service Task {} no longer exists", name, service_task.id()),
+            Err(err) => warn!("The JSON document of variable {} of Service Task {} cannot be fetched: {}", name, service_task.id(), err),
+        }
+    }
+}
+
+/// Apply the configured [UnhandledTaskPolicy] to a fetched task without a registered handler.
+/// Returns whether the task was unlocked.
+async fn handle_unhandled_task(client: &OperatonClient, config: &ConfigParams, service_task: &ServiceTask) -> bool {
+    let policy = config.unhandled_task_policy();
+    warn!(
+        "No function found for Service Task: {:#?} (topic {:#?}). Apply policy {:?}.",
+        service_task.activity_id(),
+        service_task.topic_name(),
+        policy
+    );
+
+    let message = format!(
+        "No handler registered for activity '{}' on worker '{}'",
+        service_task.activity_id(),
+        client.worker_id()
+    );
+
+    let result = match policy {
+        UnhandledTaskPolicy::Unlock => client.unlock_external_task(service_task.id()).await,
+        UnhandledTaskPolicy::Failure => {
+            // The retries are decreased like for a failed handler, so the task eventually becomes an incident
+            let (retries, retry_timeout) = config.retry_policy().next_failure(service_task.retries());
+            client.report_external_task_failure(service_task.id(), &message, None, retries, retry_timeout as i64).await
+        }
+        UnhandledTaskPolicy::Incident => {
+            client.report_external_task_failure(service_task.id(), &message, None, 0, 0).await
+        }
+    };
+
+    if let Err(err) = result {
+        error!("Could not apply policy {:?} to unhandled task {}: {}", policy, service_task.id(), err);
+    }
+    policy == UnhandledTaskPolicy::Unlock
+}
+
+/// Build the topic subscriptions for the fetchAndLock request from the registered handlers
+fn topic_subscriptions(config: &ConfigParams) -> Vec<TopicSubscription> {
+    registry::all_topics()
+        .into_iter()
+        .map(|topic| {
+            let filter = config
+                .subscription_filter(topic)
+                .cloned()
+                .unwrap_or_else(|| registry::subscription_filter_for_topic(topic));
+
+            TopicSubscription {
+                topic_name: topic.to_string(),
+                lock_duration: lock_duration_for_topic(config, topic),
+                variables: registry::variables_for_topic(topic),
+                local_variables: registry::local_variables_for_topic(topic),
+                tenant_id_in: (!config.tenant_id_in().is_empty()).then(|| config.tenant_id_in().to_vec()),
+                without_tenant_id: config.without_tenant_id(),
+                process_definition_key_in: (!filter.process_definition_key_in.is_empty())
+                    .then_some(filter.process_definition_key_in),
+                process_definition_version_tag: filter.process_definition_version_tag,
+                business_key: filter.business_key,
+                process_variables: (!filter.process_variables.is_empty()).then_some(filter.process_variables),
+                deserialize_values: false,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn service_task(id: &str) -> ServiceTask {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "activityId": "ServiceTask_Unknown",
+            "processInstanceId": "instance-1",
+            "topicName": "unknown_topic",
+            "priority": 0,
+            "businessKey": null,
+            "workerId": "operaton_task_worker"
+        }))
+        .unwrap()
+    }
+
+    fn client(server: &MockServer, config: &ConfigParams) -> OperatonClient {
+        OperatonClient::new(&config.clone().with_url(url::Url::parse(&server.uri()).unwrap())).unwrap()
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "The token endpoint rejected the client credentials")]
+    async fn test_rejected_client_credentials_are_not_blamed_on_the_engine() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({ "error": "invalid_client" })))
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default().with_startup_check(StartupCheck::Fail).with_oauth2_client_credentials(
+            url::Url::parse(&format!("{}/token", server.uri())).unwrap(),
+            "worker".to_string(),
+            "wrong".to_string(),
+            None,
+        );
+        check_engine(&client(&server, &config), &config).await;
+    }
+
+    #[tokio::test]
+    async fn test_tasks_without_handler_are_unlocked() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/unlock"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default().with_unhandled_task_policy(UnhandledTaskPolicy::Unlock);
+        let client = client(&server, &config);
+        assert!(handle_service_task(&client, &config, None, service_task("task-1"), None).await);
+    }
+
+    #[tokio::test]
+    async fn test_failures_of_tasks_without_handler_decrease_the_retries() {
+        use wiremock::matchers::body_partial_json;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/engine-rest/external-task/task-1/failure"))
+            .and(body_partial_json(serde_json::json!({ "retries": 1, "retryTimeout": 1000 })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = ConfigParams::default()
+            .with_unhandled_task_policy(UnhandledTaskPolicy::Failure)
+            .with_retry_policy(RetryPolicy { backoff: Backoff::Fixed, max_retries: 3, delay: 1_000, max_delay: 0 });
+        let client = client(&server, &config);
+
+        let mut task = serde_json::to_value(service_task("task-1")).unwrap();
+        task["retries"] = serde_json::json!(2);
+        assert!(!handle_service_task(&client, &config, None, serde_json::from_value(task).unwrap(), None).await);
+    }
+
+    #[test]
+    fn test_unlock_backoff_doubles_up_to_a_limit() {
+        let config = ConfigParams::default().with_poll_interval(500);
+        assert_eq!(unlock_backoff(&config, 1), 500);
+        assert_eq!(unlock_backoff(&config, 2), 1_000);
+        assert_eq!(unlock_backoff(&config, 4), 4_000);
+        assert_eq!(unlock_backoff(&config, 20), MAX_UNLOCK_BACKOFF);
+
+        // Without a poll interval, e.g. with long polling, the worker still backs off
+        assert_eq!(unlock_backoff(&config.with_poll_interval(0), 3), 4);
+    }
+
+    #[tokio::test]
+    async fn test_json_documents_are_fetched_for_metadata() {
+        use wiremock::matchers::query_param;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/engine-rest/variable-instance"))
+            .and(query_param("variableName", "order"))
+            .and(query_param("deserializeValues", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"id": "var-1", "name": "order", "type": "Json", "value": "{\"id\":4711}", "activityInstanceId": "instance-1", "valueInfo": {}}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut task: ServiceTask = serde_json::from_value(serde_json::json!({
+            "id": "task-1",
+            "activityId": "ServiceTask_Order",
+            "processInstanceId": "instance-1",
+            "topicName": "order",
+            "priority": 0,
+            "businessKey": null,
+            "workerId": "operaton_task_worker",
+            "variables": {
+                "order": {"type": "Json", "value": {"dataFormatName": "application/json", "nodeType": "OBJECT", "object": true}, "valueInfo": {}},
+                "customer": {"type": "Json", "value": "{\"id\":42}", "valueInfo": {}}
+            }
+        }))
+        .unwrap();
+        assert!(task.try_variables().is_err());
+
+        let config = ConfigParams::default();
+        resolve_json_documents(&client(&server, &config), &mut task).await;
+
+        let variables = task.try_variables().unwrap();
+        assert_eq!(variables["order"].as_json(), Some(&serde_json::json!({ "id": 4711 })));
+        assert_eq!(variables["customer"].as_json(), Some(&serde_json::json!({ "id": 42 })));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_start_for_the_whole_batch() {
+        let server = MockServer::start().await;
+        for id in ["task-1", "task-2"] {
+            Mock::given(method("POST"))
+                .and(path(format!("/engine-rest/external-task/{id}/extendLock")))
+                .respond_with(ResponseTemplate::new(204))
+                .expect(1..)
+                .mount(&server)
+                .await;
+        }
+
+        let config = ConfigParams::default().with_lock_duration(50);
+        let client = client(&server, &config);
+
+        // The second task is still waiting for its handler while the lock of both tasks is extended
+        let batch = start_heartbeats(&client, &config, vec![service_task("task-1"), service_task("task-2")]);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        for (_, heartbeat) in batch {
+            stop_heartbeat(heartbeat).await;
+        }
     }
-}
\ No newline at end of file
+}
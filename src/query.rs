@@ -0,0 +1,224 @@
+//! This module includes the query builder for the administrative external task queries
+
+use serde::Serialize;
+
+/// The properties external tasks can be sorted by
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExternalTaskSortBy {
+    Id,
+    LockExpirationTime,
+    ProcessInstanceId,
+    ProcessDefinitionId,
+    ProcessDefinitionKey,
+    TenantId,
+    TaskPriority,
+    CreateTime,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct Sorting {
+    sort_by: ExternalTaskSortBy,
+    sort_order: SortOrder,
+}
+
+/// A query for external tasks, used with [crate::OperatonClient::query_external_tasks] and
+/// [crate::OperatonClient::count_external_tasks]. Without any filter, the query matches all external tasks.
+///
+/// ```rust
+/// use operaton_task_worker::query::{ExternalTaskQuery, ExternalTaskSortBy, SortOrder};
+///
+/// let query = ExternalTaskQuery::default()
+///     .with_topic_name("invoicing")
+///     .with_locked(false)
+///     .with_retries_left(true)
+///     .with_sorting(ExternalTaskSortBy::TaskPriority, SortOrder::Desc)
+///     .with_paging(0, 50);
+/// ```
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalTaskQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_task_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topic_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    worker_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    activity_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process_instance_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process_definition_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tenant_id_in: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    locked: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    not_locked: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    with_retries_left: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    no_retries_left: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    active: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    suspended: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority_higher_than_or_equals: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority_lower_than_or_equals: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sorting: Vec<Sorting>,
+    /// The pagination is sent as query parameters
+    #[serde(skip)]
+    first_result: Option<u64>,
+    #[serde(skip)]
+    max_results: Option<u64>,
+}
+
+impl ExternalTaskQuery {
+    pub fn with_external_task_id(self, external_task_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.external_task_id = Some(external_task_id.into());
+        cloned_self
+    }
+
+    pub fn with_topic_name(self, topic_name: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.topic_name = Some(topic_name.into());
+        cloned_self
+    }
+
+    pub fn with_worker_id(self, worker_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.worker_id = Some(worker_id.into());
+        cloned_self
+    }
+
+    pub fn with_activity_id(self, activity_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.activity_id = Some(activity_id.into());
+        cloned_self
+    }
+
+    pub fn with_process_instance_id(self, process_instance_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.process_instance_id = Some(process_instance_id.into());
+        cloned_self
+    }
+
+    pub fn with_process_definition_id(self, process_definition_id: impl Into<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.process_definition_id = Some(process_definition_id.into());
+        cloned_self
+    }
+
+    pub fn with_tenant_ids(self, tenant_ids: Vec<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tenant_id_in = Some(tenant_ids);
+        cloned_self
+    }
+
+    /// Only tasks which are currently locked (`true`) or not locked (`false`)
+    pub fn with_locked(self, locked: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.locked = locked;
+        cloned_self.not_locked = !locked;
+        cloned_self
+    }
+
+    /// Only tasks with retries left (`true`) or without retries left, i.e. with an incident (`false`)
+    pub fn with_retries_left(self, retries_left: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.with_retries_left = retries_left;
+        cloned_self.no_retries_left = !retries_left;
+        cloned_self
+    }
+
+    /// Only suspended tasks (`true`) or active tasks (`false`)
+    pub fn with_suspended(self, suspended: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.suspended = suspended;
+        cloned_self.active = !suspended;
+        cloned_self
+    }
+
+    /// Only tasks with a priority within the range, both bounds are inclusive
+    pub fn with_priority_range(self, min: Option<i64>, max: Option<i64>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.priority_higher_than_or_equals = min;
+        cloned_self.priority_lower_than_or_equals = max;
+        cloned_self
+    }
+
+    /// Sort the results; further sortings apply to results which are equal for the previous sortings
+    pub fn with_sorting(self, sort_by: ExternalTaskSortBy, sort_order: SortOrder) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.sorting.push(Sorting { sort_by, sort_order });
+        cloned_self
+    }
+
+    /// Return `max_results` results starting at the index `first_result`
+    pub fn with_paging(self, first_result: u64, max_results: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.first_result = Some(first_result);
+        cloned_self.max_results = Some(max_results);
+        cloned_self
+    }
+
+    /// The pagination as query parameters
+    pub(crate) fn paging(&self) -> Vec<(&'static str, u64)> {
+        let mut paging = Vec::new();
+        if let Some(first_result) = self.first_result {
+            paging.push(("firstResult", first_result));
+        }
+        if let Some(max_results) = self.max_results {
+            paging.push(("maxResults", max_results));
+        }
+        paging
+    }
+
+    /// The query without sorting, which the count of the results does not depend on
+    pub(crate) fn without_sorting(&self) -> Self {
+        Self { sorting: Vec::new(), ..self.clone() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query_body() {
+        let query = ExternalTaskQuery::default()
+            .with_topic_name("invoicing")
+            .with_locked(false)
+            .with_retries_left(true)
+            .with_priority_range(Some(10), None)
+            .with_sorting(ExternalTaskSortBy::TaskPriority, SortOrder::Desc)
+            .with_paging(20, 10);
+
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            serde_json::json!({
+                "topicName": "invoicing",
+                "notLocked": true,
+                "withRetriesLeft": true,
+                "priorityHigherThanOrEquals": 10,
+                "sorting": [{ "sortBy": "taskPriority", "sortOrder": "desc" }]
+            })
+        );
+        assert_eq!(query.paging(), vec![("firstResult", 20), ("maxResults", 10)]);
+        assert!(query.without_sorting().sorting.is_empty());
+    }
+}
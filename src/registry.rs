@@ -1,13 +1,66 @@
-use crate::types::ExternalTaskFn;
+use std::collections::HashMap;
+
+use crate::retry::RetryPolicyOverride;
+use crate::settings::SubscriptionFilter;
+use crate::structures::service_task::ServiceTask;
+use crate::types::HandlerFn;
 
 pub struct Handler {
     pub name: &'static str,
-    pub func: ExternalTaskFn,
+    /// The topic to subscribe to for this handler. Defaults to `name` when `None`.
+    pub topic: Option<&'static str>,
+    /// The lock duration in milliseconds for this handler's topic. Defaults to the configured lock duration when `None`.
+    pub lock_duration: Option<u64>,
+    /// The variables to fetch with the task. All variables visible to the task are fetched when `None`.
+    pub variables: Option<&'static [&'static str]>,
+    /// Whether only the local variables of the task's execution are fetched
+    pub local_variables: bool,
+    /// Overrides of the configured retry policy for failures of this handler
+    pub retry_policy: RetryPolicyOverride,
+    /// Restricts the tasks this handler is responsible for
+    pub filter: TaskFilter,
+    /// The handler function, use [HandlerFn::Plain] for a function without the task context argument
+    pub func: HandlerFn,
+}
+
+/// Restricts the tasks a handler is responsible for. Empty fields do not restrict the tasks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskFilter {
+    pub process_definition_keys: &'static [&'static str],
+    pub process_definition_version_tag: Option<&'static str>,
+    pub business_key: Option<&'static str>,
+    /// The variable names and their JSON encoded values
+    pub process_variables: &'static [(&'static str, &'static str)],
+}
+
+impl TaskFilter {
+    /// No restrictions, the handler is responsible for all tasks of its activity or topic
+    pub const NONE: TaskFilter = TaskFilter {
+        process_definition_keys: &[],
+        process_definition_version_tag: None,
+        business_key: None,
+        process_variables: &[],
+    };
+
+    /// Whether a fetched task matches the process definition key, version tag and business key of the filter.
+    /// Process variables are only filtered by the engine.
+    pub fn matches(&self, task: &ServiceTask) -> bool {
+        (self.process_definition_keys.is_empty()
+            || task.process_definition_key().is_some_and(|key| self.process_definition_keys.contains(&key)))
+            && self.process_definition_version_tag.is_none_or(|tag| task.process_definition_version_tag() == Some(tag))
+            && self.business_key.is_none_or(|key| task.business_key().as_deref() == Some(key))
+    }
+}
+
+impl Handler {
+    pub fn topic(&self) -> &'static str {
+        self.topic.unwrap_or(self.name)
+    }
 }
 
 inventory::collect!(Handler);
 
-pub fn find(name: &str) -> Option<ExternalTaskFn> {
+pub fn find(name: &str) -> Option<HandlerFn> {
     for h in inventory::iter::<Handler> {
         if h.name == name {
             return Some(h.func);
@@ -16,7 +69,117 @@ pub fn find(name: &str) -> Option<ExternalTaskFn> {
     None
 }
 
+/// Find the handler for a fetched task: a handler registered for the activity id takes precedence,
+/// otherwise a handler registered with the topic name as its name is used.
+pub fn find_for_task(activity_id: &str, topic_name: &str) -> Option<HandlerFn> {
+    find_handler_for_task(activity_id, topic_name).map(|h| h.func)
+}
+
+/// Like [find_for_task], but returns the registered [Handler] including its settings
+pub fn find_handler_for_task(activity_id: &str, topic_name: &str) -> Option<&'static Handler> {
+    let find_handler = |name: &str| inventory::iter::<Handler>.into_iter().find(|h| h.name == name);
+    find_handler(activity_id).or_else(|| find_handler(topic_name))
+}
+
+/// Find the handler for a fetched task like [find_handler_for_task], but only consider handlers whose
+/// [TaskFilter] matches the task, so handlers of the same activity or topic can serve different processes
+pub fn find_handler_for_service_task(task: &ServiceTask) -> Option<&'static Handler> {
+    let find_handler = |name: &str| {
+        inventory::iter::<Handler>
+            .into_iter()
+            .find(|h| h.name == name && h.filter.matches(task))
+    };
+    find_handler(task.activity_id()).or_else(|| find_handler(task.topic_name()))
+}
+
 pub fn all_names() -> Vec<&'static str> {
     inventory::iter::<Handler>.into_iter().map(|h| h.name).collect()
 }
 
+/// All distinct topics of the registered handlers
+pub fn all_topics() -> Vec<&'static str> {
+    let mut topics: Vec<&'static str> = Vec::new();
+    for h in inventory::iter::<Handler> {
+        if !topics.contains(&h.topic()) {
+            topics.push(h.topic());
+        }
+    }
+    topics
+}
+
+/// The lock duration for a topic: the largest lock duration requested by any handler of the topic, if any.
+pub fn lock_duration_for_topic(topic: &str) -> Option<u64> {
+    inventory::iter::<Handler>
+        .into_iter()
+        .filter(|h| h.topic() == topic)
+        .filter_map(|h| h.lock_duration)
+        .max()
+}
+
+/// The variables to fetch for a topic: the union of the variables requested by the handlers of the topic,
+/// or `None` (all variables) if any handler of the topic requests all variables.
+pub fn variables_for_topic(topic: &str) -> Option<Vec<String>> {
+    let mut variables: Vec<String> = Vec::new();
+    for h in inventory::iter::<Handler>.into_iter().filter(|h| h.topic() == topic) {
+        for variable in h.variables? {
+            if !variables.iter().any(|v| v == variable) {
+                variables.push(variable.to_string());
+            }
+        }
+    }
+    Some(variables)
+}
+
+/// Whether only local variables are fetched for a topic, which requires all handlers of the topic to request local variables
+pub fn local_variables_for_topic(topic: &str) -> bool {
+    inventory::iter::<Handler>
+        .into_iter()
+        .filter(|h| h.topic() == topic)
+        .all(|h| h.local_variables)
+}
+
+/// The subscription filter for a topic, combined from the filters of the handlers of the topic.
+///
+/// The subscription has to fetch the tasks of all handlers of the topic, so process definition keys are joined and
+/// a filter only applies if it is set by every handler of the topic.
+pub fn subscription_filter_for_topic(topic: &str) -> SubscriptionFilter {
+    let filters: Vec<&TaskFilter> = inventory::iter::<Handler>
+        .into_iter()
+        .filter(|h| h.topic() == topic)
+        .map(|h| &h.filter)
+        .collect();
+
+    let mut filter = SubscriptionFilter::default();
+    if filters.is_empty() {
+        return filter;
+    }
+
+    if filters.iter().all(|f| !f.process_definition_keys.is_empty()) {
+        for key in filters.iter().flat_map(|f| f.process_definition_keys) {
+            if !filter.process_definition_key_in.iter().any(|k| k == key) {
+                filter.process_definition_key_in.push(key.to_string());
+            }
+        }
+    }
+
+    filter.process_definition_version_tag = common_value(&filters, |f| f.process_definition_version_tag);
+    filter.business_key = common_value(&filters, |f| f.business_key);
+
+    let mut process_variables = HashMap::new();
+    for (name, value) in filters[0].process_variables {
+        if filters.iter().all(|f| f.process_variables.contains(&(name, value)))
+            && let Ok(value) = serde_json::from_str(value)
+        {
+            process_variables.insert(name.to_string(), value);
+        }
+    }
+    filter.process_variables = process_variables;
+
+    filter
+}
+
+/// The value which is set to the same value by all filters, if any
+fn common_value(filters: &[&TaskFilter], value: impl Fn(&TaskFilter) -> Option<&'static str>) -> Option<String> {
+    let first = value(filters[0])?;
+    filters.iter().all(|f| value(f) == Some(first)).then(|| first.to_string())
+}
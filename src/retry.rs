@@ -0,0 +1,161 @@
+//! This module includes the retry policy for failed task executions
+
+use serde::{Deserialize, Serialize};
+
+/// The backoff strategy between retries of a failed task
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backoff {
+    /// Wait `delay` milliseconds before every retry (default)
+    #[default]
+    Fixed,
+    /// Wait `n * delay` milliseconds before the n-th retry
+    Linear,
+    /// Wait `2^(n-1) * delay` milliseconds before the n-th retry
+    Exponential,
+}
+
+/// The retry policy for technical failures of a handler.
+///
+/// The engine keeps track of the remaining retries of an external task. On the first failure, the task
+/// gets `max_retries` retries; every further failure decrements them. The failure which leaves no
+/// retries creates an incident.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub backoff: Backoff,
+    /// The number of retries after the first failed attempt
+    pub max_retries: u32,
+    /// The base delay in milliseconds between retries
+    pub delay: u64,
+    /// The maximum delay in milliseconds between retries, `0` for no limit
+    pub max_delay: u64,
+}
+
+/// Per-handler overrides of the configured [RetryPolicy]. `None` keeps the configured value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetryPolicyOverride {
+    pub backoff: Option<Backoff>,
+    pub max_retries: Option<u32>,
+    pub delay: Option<u64>,
+    pub max_delay: Option<u64>,
+}
+
+impl RetryPolicyOverride {
+    /// No overrides, the configured policy applies
+    pub const NONE: RetryPolicyOverride = RetryPolicyOverride {
+        backoff: None,
+        max_retries: None,
+        delay: None,
+        max_delay: None,
+    };
+}
+
+impl RetryPolicy {
+    /// Apply the overrides of a handler to this policy
+    pub fn with_override(self, overrides: &RetryPolicyOverride) -> Self {
+        Self {
+            backoff: overrides.backoff.unwrap_or(self.backoff),
+            max_retries: overrides.max_retries.unwrap_or(self.max_retries),
+            delay: overrides.delay.unwrap_or(self.delay),
+            max_delay: overrides.max_delay.unwrap_or(self.max_delay),
+        }
+    }
+
+    /// The delay in milliseconds before the n-th retry (starting at 1)
+    pub fn delay_for_retry(&self, retry: u32) -> u64 {
+        let retry = retry.max(1);
+        let delay = match self.backoff {
+            Backoff::Fixed => self.delay,
+            Backoff::Linear => self.delay.saturating_mul(retry as u64),
+            Backoff::Exponential => self.delay.saturating_mul(2u64.saturating_pow(retry - 1)),
+        };
+
+        if self.max_delay > 0 { delay.min(self.max_delay) } else { delay }
+    }
+
+    /// The delay in milliseconds before the n-th retry with a random jitter of up to half of the delay,
+    /// so clients which failed at the same time do not retry at the same time
+    pub fn jittered_delay_for_retry(&self, retry: u32) -> u64 {
+        let delay = self.delay_for_retry(retry);
+        delay - fastrand::u64(0..=delay / 2)
+    }
+
+    /// The remaining retries and the retry timeout in milliseconds to report for a failure.
+    ///
+    /// `current_retries` are the retries of the fetched external task, which are `None` before the first failure.
+    pub fn next_failure(&self, current_retries: Option<i32>) -> (i32, u64) {
+        let max_retries = self.max_retries.min(i32::MAX as u32) as i32;
+        let remaining = match current_retries {
+            None => max_retries,
+            Some(retries) => (retries - 1).max(0),
+        };
+
+        if remaining == 0 {
+            return (0, 0);
+        }
+
+        let retry = (max_retries - remaining + 1).max(1) as u32;
+        (remaining, self.delay_for_retry(retry))
+    }
+}
+
+impl Default for RetryPolicy {
+    /// No retries, every failure creates an incident
+    fn default() -> Self {
+        Self {
+            backoff: Backoff::Fixed,
+            max_retries: 0,
+            delay: 0,
+            max_delay: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exponential_backoff_decrements_retries() {
+        let policy = RetryPolicy { backoff: Backoff::Exponential, max_retries: 3, delay: 1_000, max_delay: 3_000 };
+
+        assert_eq!(policy.next_failure(None), (3, 1_000));
+        assert_eq!(policy.next_failure(Some(3)), (2, 2_000));
+        assert_eq!(policy.next_failure(Some(2)), (1, 3_000));
+        assert_eq!(policy.next_failure(Some(1)), (0, 0));
+    }
+
+    #[test]
+    fn test_fixed_and_linear_backoff() {
+        let fixed = RetryPolicy { backoff: Backoff::Fixed, max_retries: 2, delay: 500, max_delay: 0 };
+        assert_eq!(fixed.delay_for_retry(1), 500);
+        assert_eq!(fixed.delay_for_retry(2), 500);
+
+        let linear = RetryPolicy { backoff: Backoff::Linear, ..fixed };
+        assert_eq!(linear.delay_for_retry(1), 500);
+        assert_eq!(linear.delay_for_retry(3), 1_500);
+    }
+
+    #[test]
+    fn test_jittered_delay() {
+        let policy = RetryPolicy { backoff: Backoff::Exponential, max_retries: 3, delay: 1_000, max_delay: 3_000 };
+        for _ in 0..100 {
+            let delay = policy.jittered_delay_for_retry(2);
+            assert!((1_000..=2_000).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_default_policy_creates_incident_right_away() {
+        assert_eq!(RetryPolicy::default().next_failure(None), (0, 0));
+    }
+
+    #[test]
+    fn test_override() {
+        let overrides = RetryPolicyOverride { max_retries: Some(5), ..RetryPolicyOverride::NONE };
+        let policy = RetryPolicy { backoff: Backoff::Linear, max_retries: 1, delay: 100, max_delay: 0 }.with_override(&overrides);
+        assert_eq!(policy.max_retries, 5);
+        assert_eq!(policy.backoff, Backoff::Linear);
+        assert_eq!(policy.delay, 100);
+    }
+}
@@ -11,17 +11,31 @@ pub fn load_config_from_env(env_prefix: &str) -> ConfigParams {
     settings.try_deserialize().unwrap()
 }
 
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
 use url::Url;
 
+use crate::retry::{Backoff, RetryPolicy};
+
 /// The struct contains all config params for running the task worker
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ConfigParams {
     /// The URL where operaton can be found
     #[serde(default = "default_url")]
     url: Url,
 
+    /// The path of the REST API relative to `url`
+    #[serde(default = "default_rest_root")]
+    rest_root: String,
+
+    /// The name of the process engine to address
+    /// - If not set, the default process engine is used (default).
+    #[serde(default)]
+    engine_name: Option<String>,
+
     /// The username for authenticating with the REST API
     /// - If empty, no authentication will be used (default).
     #[serde(default = "String::new")]
@@ -32,6 +46,28 @@ pub struct ConfigParams {
     #[serde(default = "String::new")]
     password: String,
 
+    /// A static bearer token for authenticating with the REST API
+    /// - If empty, no bearer token will be used (default).
+    #[serde(default = "String::new")]
+    bearer_token: String,
+
+    /// The token endpoint for the OAuth2 client credentials flow
+    /// - If set, the worker authenticates with an OAuth2 access token instead of a bearer token or username and password.
+    #[serde(default)]
+    oauth2_token_url: Option<Url>,
+
+    /// The client id for the OAuth2 client credentials flow
+    #[serde(default = "String::new")]
+    oauth2_client_id: String,
+
+    /// The client secret for the OAuth2 client credentials flow
+    #[serde(default = "String::new")]
+    oauth2_client_secret: String,
+
+    /// The scope requested with the OAuth2 client credentials flow
+    #[serde(default)]
+    oauth2_scope: Option<String>,
+
     /// The interval in milliseconds for polling the Operaton Task Worker for new tasks
     #[serde(default = "default_poll_interval")]
     poll_interval: usize,
@@ -43,6 +79,265 @@ pub struct ConfigParams {
     /// The lock duration in milliseconds for external task locking
     #[serde(default = "default_lock_duration")]
     lock_duration: u64,
+
+    /// The maximum number of tasks to fetch and lock in one request
+    #[serde(default = "default_max_tasks")]
+    max_tasks: usize,
+
+    /// Whether tasks with a higher priority are fetched first
+    #[serde(default)]
+    use_priority: bool,
+
+    /// Only fetch tasks of these tenants, given as list or comma separated string
+    /// - If empty, tasks of all tenants are fetched (default).
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    tenant_id_in: Vec<String>,
+
+    /// Only fetch tasks which do not belong to a tenant
+    #[serde(default)]
+    without_tenant_id: bool,
+
+    /// Filters for the subscription of a topic, by topic name, given as map or JSON object.
+    /// A configured filter replaces the filter of the topic's handlers.
+    #[serde(default, deserialize_with = "deserialize_subscription_filters")]
+    subscription_filters: HashMap<String, SubscriptionFilter>,
+
+    /// The long polling timeout in milliseconds. The engine holds the fetch request open until
+    /// tasks are available or the timeout elapses.
+    /// - If `0`, long polling is disabled and the worker polls every `poll_interval` milliseconds (default).
+    #[serde(default)]
+    async_response_timeout: u64,
+
+    /// The timeout in milliseconds for establishing a connection to the REST API
+    #[serde(default = "default_connect_timeout")]
+    connect_timeout: u64,
+
+    /// The timeout in milliseconds for a complete request to the REST API
+    /// - If `0`, requests do not time out. Long polling requests always use a timeout longer than `async_response_timeout`.
+    #[serde(default = "default_request_timeout")]
+    request_timeout: u64,
+
+    /// The TCP keep-alive interval in milliseconds
+    /// - If `0`, TCP keep-alive is disabled.
+    #[serde(default = "default_tcp_keepalive")]
+    tcp_keepalive: u64,
+
+    /// The time in milliseconds an idle connection is kept in the connection pool
+    #[serde(default = "default_pool_idle_timeout")]
+    pool_idle_timeout: u64,
+
+    /// The maximum number of idle connections kept in the connection pool
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pool_max_idle_per_host: usize,
+
+    /// A PEM file with additional CA certificates to trust, e.g. of a company CA
+    #[serde(default)]
+    tls_ca_bundle: Option<PathBuf>,
+
+    /// The client certificate for mutual TLS: a PEM certificate (requires `tls_client_key`)
+    /// or a PKCS#12 archive (`.p12`/`.pfx`)
+    #[serde(default)]
+    tls_client_cert: Option<PathBuf>,
+
+    /// The PEM private key of the client certificate
+    #[serde(default)]
+    tls_client_key: Option<PathBuf>,
+
+    /// The password of a PKCS#12 client certificate
+    #[serde(default)]
+    tls_client_cert_password: Option<String>,
+
+    /// Whether invalid server certificates are accepted. Only use this for local development.
+    #[serde(default)]
+    tls_insecure_skip_verify: bool,
+
+    /// The file of the durable outbox, which stores the results of tasks until the engine accepted them
+    /// - If not set, results are sent without outbox and are lost if the engine is unreachable (default).
+    #[serde(default)]
+    outbox_path: Option<PathBuf>,
+
+    /// The initial delay in milliseconds between attempts to send a result from the outbox, which doubles with every attempt
+    #[serde(default = "default_outbox_retry_delay")]
+    outbox_retry_delay: u64,
+
+    /// The maximum delay in milliseconds between attempts to send a result from the outbox
+    #[serde(default = "default_outbox_retry_max_delay")]
+    outbox_retry_max_delay: u64,
+
+    /// The number of retries of a request to the REST API which failed with a transient error,
+    /// e.g. a connection reset or a 502/503 from a load balancer
+    #[serde(default = "default_http_max_retries")]
+    http_max_retries: u32,
+
+    /// The initial delay in milliseconds before retrying a request, which doubles with every retry
+    #[serde(default = "default_http_retry_delay")]
+    http_retry_delay: u64,
+
+    /// The maximum delay in milliseconds before retrying a request, which also limits the `Retry-After` of the engine
+    /// - If `0`, the backoff is not limited and the `Retry-After` of the engine is limited to one minute.
+    #[serde(default = "default_http_retry_max_delay")]
+    http_retry_max_delay: u64,
+
+    /// What to do if the engine version check at startup fails
+    #[serde(default)]
+    startup_check: StartupCheck,
+
+    /// The directory with `.bpmn`, `.dmn` and `.form` files which are deployed at startup, before polling starts
+    /// - If not set, nothing is deployed (default).
+    #[serde(default)]
+    deployment_dir: Option<PathBuf>,
+
+    /// The name of the deployment. Duplicates are detected by comparing with the last deployment with this name.
+    /// - If not set, the worker id is used (default).
+    #[serde(default)]
+    deployment_name: Option<String>,
+
+    /// The source of the deployment
+    #[serde(default = "default_deployment_source")]
+    deployment_source: String,
+
+    /// Whether only changed resources are deployed again (`true`, default) or all resources of the deployment
+    /// once one of them changed (`false`)
+    #[serde(default = "default_deploy_changed_only")]
+    deploy_changed_only: bool,
+
+    /// Whether the lock of a fetched task is extended periodically until its handler finishes
+    #[serde(default = "default_auto_extend_lock")]
+    auto_extend_lock: bool,
+
+    /// What to do with a fetched task for which no handler is registered
+    #[serde(default)]
+    unhandled_task_policy: UnhandledTaskPolicy,
+
+    /// Whether a task with variables which cannot be parsed fails with an incident instead of running its handler
+    /// without those variables
+    #[serde(default)]
+    strict_variables: bool,
+
+    /// The backoff strategy between retries of a failed task: `fixed`, `linear` or `exponential`
+    #[serde(default)]
+    retry_backoff: Backoff,
+
+    /// The number of retries of a failed task before an incident is created
+    /// - If `0`, the first failure creates an incident (default).
+    #[serde(default)]
+    max_retries: u32,
+
+    /// The base delay in milliseconds between retries of a failed task
+    #[serde(default = "default_retry_delay")]
+    retry_delay: u64,
+
+    /// The maximum delay in milliseconds between retries of a failed task
+    /// - If `0`, the delay is not limited (default).
+    #[serde(default)]
+    retry_max_delay: u64,
+}
+
+impl std::fmt::Debug for ConfigParams {
+    /// Secrets are redacted, as the configuration is logged at startup
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigParams")
+            .field("url", &self.url)
+            .field("rest_root", &self.rest_root)
+            .field("engine_name", &self.engine_name)
+            .field("username", &self.username)
+            .field("password", &redact(&self.password))
+            .field("bearer_token", &redact(&self.bearer_token))
+            .field("oauth2_token_url", &self.oauth2_token_url)
+            .field("oauth2_client_id", &self.oauth2_client_id)
+            .field("oauth2_client_secret", &redact(&self.oauth2_client_secret))
+            .field("oauth2_scope", &self.oauth2_scope)
+            .field("poll_interval", &self.poll_interval)
+            .field("id", &self.id)
+            .field("lock_duration", &self.lock_duration)
+            .field("max_tasks", &self.max_tasks)
+            .field("use_priority", &self.use_priority)
+            .field("tenant_id_in", &self.tenant_id_in)
+            .field("without_tenant_id", &self.without_tenant_id)
+            .field("subscription_filters", &self.subscription_filters)
+            .field("async_response_timeout", &self.async_response_timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .field("tcp_keepalive", &self.tcp_keepalive)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("tls_ca_bundle", &self.tls_ca_bundle)
+            .field("tls_client_cert", &self.tls_client_cert)
+            .field("tls_client_key", &self.tls_client_key)
+            .field("tls_client_cert_password", &self.tls_client_cert_password.as_deref().map(redact))
+            .field("tls_insecure_skip_verify", &self.tls_insecure_skip_verify)
+            .field("outbox_path", &self.outbox_path)
+            .field("outbox_retry_delay", &self.outbox_retry_delay)
+            .field("outbox_retry_max_delay", &self.outbox_retry_max_delay)
+            .field("http_max_retries", &self.http_max_retries)
+            .field("http_retry_delay", &self.http_retry_delay)
+            .field("http_retry_max_delay", &self.http_retry_max_delay)
+            .field("startup_check", &self.startup_check)
+            .field("deployment_dir", &self.deployment_dir)
+            .field("deployment_name", &self.deployment_name)
+            .field("deployment_source", &self.deployment_source)
+            .field("deploy_changed_only", &self.deploy_changed_only)
+            .field("auto_extend_lock", &self.auto_extend_lock)
+            .field("unhandled_task_policy", &self.unhandled_task_policy)
+            .field("strict_variables", &self.strict_variables)
+            .field("retry_backoff", &self.retry_backoff)
+            .field("max_retries", &self.max_retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("retry_max_delay", &self.retry_max_delay)
+            .finish()
+    }
+}
+
+/// Hide a secret in debug output, but show whether it is set
+fn redact(secret: &str) -> &'static str {
+    if secret.is_empty() { "" } else { "<redacted>" }
+}
+
+/// Restricts the tasks fetched for a topic subscription. Empty fields do not restrict the tasks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SubscriptionFilter {
+    /// Only fetch tasks of these process definitions
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    pub process_definition_key_in: Vec<String>,
+    /// Only fetch tasks of process definitions with this version tag
+    #[serde(default)]
+    pub process_definition_version_tag: Option<String>,
+    /// Only fetch tasks of process instances with this business key
+    #[serde(default)]
+    pub business_key: Option<String>,
+    /// Only fetch tasks of process instances with these variable values
+    #[serde(default)]
+    pub process_variables: HashMap<String, serde_json::Value>,
+}
+
+/// What to do if the engine is unreachable, rejects the credentials or runs an unsupported version at startup
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StartupCheck {
+    /// Stop the worker
+    Fail,
+    /// Log a warning and start polling anyway (default)
+    #[default]
+    Warn,
+    /// Do not check the engine
+    Off,
+}
+
+/// The policy for fetched tasks without a registered handler.
+///
+/// Tasks of topics without any registered handler are never locked, because the worker only subscribes
+/// to the topics of its handlers. The policy applies to tasks of a subscribed topic whose activity has no handler.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnhandledTaskPolicy {
+    /// Unlock the task right away, so other workers can fetch it (default). The worker backs off before fetching
+    /// again, as it may fetch the unlocked task itself.
+    #[default]
+    Unlock,
+    /// Report a failure according to the retry policy, which decreases the retries until the task becomes an incident
+    Failure,
+    /// Report a failure with no retries left, which creates an incident
+    Incident,
 }
 
 impl ConfigParams {
@@ -50,6 +345,14 @@ impl ConfigParams {
         &self.url
     }
 
+    pub fn rest_root(&self) -> &str {
+        &self.rest_root
+    }
+
+    pub fn engine_name(&self) -> Option<&str> {
+        self.engine_name.as_deref()
+    }
+
     pub fn username(&self) -> &str {
         &self.username
     }
@@ -58,6 +361,31 @@ impl ConfigParams {
         &self.password
     }
 
+    pub fn bearer_token(&self) -> &str {
+        &self.bearer_token
+    }
+
+    pub fn oauth2_token_url(&self) -> Option<&Url> {
+        self.oauth2_token_url.as_ref()
+    }
+
+    pub fn oauth2_client_id(&self) -> &str {
+        &self.oauth2_client_id
+    }
+
+    pub fn oauth2_client_secret(&self) -> &str {
+        &self.oauth2_client_secret
+    }
+
+    pub fn oauth2_scope(&self) -> Option<&str> {
+        self.oauth2_scope.as_deref()
+    }
+
+    /// Whether any authentication is configured
+    pub fn has_authentication(&self) -> bool {
+        self.oauth2_token_url.is_some() || !self.bearer_token.is_empty() || !self.username.is_empty()
+    }
+
     pub fn poll_interval(&self) -> usize {
         self.poll_interval
     }
@@ -66,12 +394,108 @@ impl ConfigParams {
 
     pub fn lock_duration(&self) -> u64 { self.lock_duration }
 
+    pub fn max_tasks(&self) -> usize { self.max_tasks }
+
+    pub fn use_priority(&self) -> bool { self.use_priority }
+
+    pub fn tenant_id_in(&self) -> &[String] { &self.tenant_id_in }
+
+    pub fn without_tenant_id(&self) -> bool { self.without_tenant_id }
+
+    /// The configured filter for the subscription of a topic
+    pub fn subscription_filter(&self, topic: &str) -> Option<&SubscriptionFilter> { self.subscription_filters.get(topic) }
+
+    pub fn async_response_timeout(&self) -> u64 { self.async_response_timeout }
+
+    pub fn long_polling(&self) -> bool { self.async_response_timeout > 0 }
+
+    pub fn connect_timeout(&self) -> u64 { self.connect_timeout }
+
+    pub fn request_timeout(&self) -> u64 { self.request_timeout }
+
+    pub fn tcp_keepalive(&self) -> u64 { self.tcp_keepalive }
+
+    pub fn pool_idle_timeout(&self) -> u64 { self.pool_idle_timeout }
+
+    pub fn pool_max_idle_per_host(&self) -> usize { self.pool_max_idle_per_host }
+
+    pub fn tls_ca_bundle(&self) -> Option<&Path> { self.tls_ca_bundle.as_deref() }
+
+    pub fn tls_client_cert(&self) -> Option<&Path> { self.tls_client_cert.as_deref() }
+
+    pub fn tls_client_key(&self) -> Option<&Path> { self.tls_client_key.as_deref() }
+
+    pub fn tls_client_cert_password(&self) -> Option<&str> { self.tls_client_cert_password.as_deref() }
+
+    pub fn tls_insecure_skip_verify(&self) -> bool { self.tls_insecure_skip_verify }
+
+    /// The exponential backoff between retries of requests which failed with a transient error
+    pub fn http_retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            backoff: Backoff::Exponential,
+            max_retries: self.http_max_retries,
+            delay: self.http_retry_delay,
+            max_delay: self.http_retry_max_delay,
+        }
+    }
+
+    pub fn outbox_path(&self) -> Option<&Path> { self.outbox_path.as_deref() }
+
+    /// The exponential backoff between attempts to send a result from the outbox
+    pub fn outbox_retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            backoff: Backoff::Exponential,
+            max_retries: u32::MAX,
+            delay: self.outbox_retry_delay,
+            max_delay: self.outbox_retry_max_delay,
+        }
+    }
+
+    pub fn startup_check(&self) -> StartupCheck { self.startup_check }
+
+    pub fn deployment_dir(&self) -> Option<&Path> { self.deployment_dir.as_deref() }
+
+    /// The name of the deployment, which defaults to the worker id
+    pub fn deployment_name(&self) -> &str { self.deployment_name.as_deref().unwrap_or(&self.id) }
+
+    pub fn deployment_source(&self) -> &str { &self.deployment_source }
+
+    pub fn deploy_changed_only(&self) -> bool { self.deploy_changed_only }
+
+    pub fn auto_extend_lock(&self) -> bool { self.auto_extend_lock }
+
+    pub fn unhandled_task_policy(&self) -> UnhandledTaskPolicy { self.unhandled_task_policy }
+
+    pub fn strict_variables(&self) -> bool { self.strict_variables }
+
+    /// The retry policy for failed tasks, which handlers may override
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            backoff: self.retry_backoff,
+            max_retries: self.max_retries,
+            delay: self.retry_delay,
+            max_delay: self.retry_max_delay,
+        }
+    }
+
     pub fn with_url(self, url: Url) -> Self {
         let mut cloned_self = self.clone();
         cloned_self.url = url;
         cloned_self
     }
 
+    pub fn with_rest_root(self, rest_root: String) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.rest_root = rest_root;
+        cloned_self
+    }
+
+    pub fn with_engine_name(self, engine_name: Option<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.engine_name = engine_name;
+        cloned_self
+    }
+
     pub fn with_auth(self, username: String, password: String) -> Self {
         let mut cloned_self = self.clone();
         cloned_self.username = username;
@@ -79,6 +503,27 @@ impl ConfigParams {
         cloned_self
     }
 
+    pub fn with_bearer_token(self, bearer_token: String) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.bearer_token = bearer_token;
+        cloned_self
+    }
+
+    pub fn with_oauth2_client_credentials(
+        self,
+        token_url: Url,
+        client_id: String,
+        client_secret: String,
+        scope: Option<String>,
+    ) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.oauth2_token_url = Some(token_url);
+        cloned_self.oauth2_client_id = client_id;
+        cloned_self.oauth2_client_secret = client_secret;
+        cloned_self.oauth2_scope = scope;
+        cloned_self
+    }
+
     pub fn with_poll_interval(self, poll_interval: usize) -> Self {
         let mut cloned_self = self.clone();
         cloned_self.poll_interval = poll_interval;
@@ -96,17 +541,207 @@ impl ConfigParams {
         cloned_self.lock_duration = lock_duration;
         cloned_self
     }
+
+    pub fn with_max_tasks(self, max_tasks: usize) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.max_tasks = max_tasks;
+        cloned_self
+    }
+
+    pub fn with_use_priority(self, use_priority: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.use_priority = use_priority;
+        cloned_self
+    }
+
+    pub fn with_tenant_filter(self, tenant_id_in: Vec<String>, without_tenant_id: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tenant_id_in = tenant_id_in;
+        cloned_self.without_tenant_id = without_tenant_id;
+        cloned_self
+    }
+
+    pub fn with_subscription_filter(self, topic: String, filter: SubscriptionFilter) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.subscription_filters.insert(topic, filter);
+        cloned_self
+    }
+
+    pub fn with_async_response_timeout(self, async_response_timeout: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.async_response_timeout = async_response_timeout;
+        cloned_self
+    }
+
+    pub fn with_connect_timeout(self, connect_timeout: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.connect_timeout = connect_timeout;
+        cloned_self
+    }
+
+    pub fn with_request_timeout(self, request_timeout: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.request_timeout = request_timeout;
+        cloned_self
+    }
+
+    pub fn with_tcp_keepalive(self, tcp_keepalive: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tcp_keepalive = tcp_keepalive;
+        cloned_self
+    }
+
+    pub fn with_connection_pool(self, pool_idle_timeout: u64, pool_max_idle_per_host: usize) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.pool_idle_timeout = pool_idle_timeout;
+        cloned_self.pool_max_idle_per_host = pool_max_idle_per_host;
+        cloned_self
+    }
+
+    pub fn with_tls_ca_bundle(self, tls_ca_bundle: Option<PathBuf>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tls_ca_bundle = tls_ca_bundle;
+        cloned_self
+    }
+
+    pub fn with_tls_client_cert(
+        self,
+        tls_client_cert: Option<PathBuf>,
+        tls_client_key: Option<PathBuf>,
+        tls_client_cert_password: Option<String>,
+    ) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tls_client_cert = tls_client_cert;
+        cloned_self.tls_client_key = tls_client_key;
+        cloned_self.tls_client_cert_password = tls_client_cert_password;
+        cloned_self
+    }
+
+    pub fn with_tls_insecure_skip_verify(self, tls_insecure_skip_verify: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.tls_insecure_skip_verify = tls_insecure_skip_verify;
+        cloned_self
+    }
+
+    pub fn with_http_retry(self, http_max_retries: u32, http_retry_delay: u64, http_retry_max_delay: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.http_max_retries = http_max_retries;
+        cloned_self.http_retry_delay = http_retry_delay;
+        cloned_self.http_retry_max_delay = http_retry_max_delay;
+        cloned_self
+    }
+
+    pub fn with_outbox(self, outbox_path: Option<PathBuf>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.outbox_path = outbox_path;
+        cloned_self
+    }
+
+    pub fn with_outbox_retry_delay(self, outbox_retry_delay: u64, outbox_retry_max_delay: u64) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.outbox_retry_delay = outbox_retry_delay;
+        cloned_self.outbox_retry_max_delay = outbox_retry_max_delay;
+        cloned_self
+    }
+
+    pub fn with_startup_check(self, startup_check: StartupCheck) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.startup_check = startup_check;
+        cloned_self
+    }
+
+    pub fn with_deployment(self, deployment_dir: Option<PathBuf>, deployment_name: Option<String>) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.deployment_dir = deployment_dir;
+        cloned_self.deployment_name = deployment_name;
+        cloned_self
+    }
+
+    pub fn with_deployment_source(self, deployment_source: String, deploy_changed_only: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.deployment_source = deployment_source;
+        cloned_self.deploy_changed_only = deploy_changed_only;
+        cloned_self
+    }
+
+    pub fn with_auto_extend_lock(self, auto_extend_lock: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.auto_extend_lock = auto_extend_lock;
+        cloned_self
+    }
+
+    pub fn with_unhandled_task_policy(self, unhandled_task_policy: UnhandledTaskPolicy) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.unhandled_task_policy = unhandled_task_policy;
+        cloned_self
+    }
+
+    pub fn with_strict_variables(self, strict_variables: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.strict_variables = strict_variables;
+        cloned_self
+    }
+
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.retry_backoff = retry_policy.backoff;
+        cloned_self.max_retries = retry_policy.max_retries;
+        cloned_self.retry_delay = retry_policy.delay;
+        cloned_self.retry_max_delay = retry_policy.max_delay;
+        cloned_self
+    }
 }
 
 impl Default for ConfigParams {
     fn default() -> Self {
         Self {
             url: default_url(),
+            rest_root: default_rest_root(),
+            engine_name: None,
             username: String::new(),
             password: String::new(),
+            bearer_token: String::new(),
+            oauth2_token_url: None,
+            oauth2_client_id: String::new(),
+            oauth2_client_secret: String::new(),
+            oauth2_scope: None,
             poll_interval: default_poll_interval(),
             id: default_task_worker_id(),
             lock_duration: default_lock_duration(),
+            max_tasks: default_max_tasks(),
+            use_priority: false,
+            tenant_id_in: Vec::new(),
+            without_tenant_id: false,
+            subscription_filters: HashMap::new(),
+            async_response_timeout: 0,
+            connect_timeout: default_connect_timeout(),
+            request_timeout: default_request_timeout(),
+            tcp_keepalive: default_tcp_keepalive(),
+            pool_idle_timeout: default_pool_idle_timeout(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            tls_ca_bundle: None,
+            tls_client_cert: None,
+            tls_client_key: None,
+            tls_client_cert_password: None,
+            tls_insecure_skip_verify: false,
+            http_max_retries: default_http_max_retries(),
+            http_retry_delay: default_http_retry_delay(),
+            http_retry_max_delay: default_http_retry_max_delay(),
+            outbox_path: None,
+            outbox_retry_delay: default_outbox_retry_delay(),
+            outbox_retry_max_delay: default_outbox_retry_max_delay(),
+            startup_check: StartupCheck::default(),
+            deployment_dir: None,
+            deployment_name: None,
+            deployment_source: default_deployment_source(),
+            deploy_changed_only: default_deploy_changed_only(),
+            auto_extend_lock: default_auto_extend_lock(),
+            unhandled_task_policy: UnhandledTaskPolicy::default(),
+            strict_variables: false,
+            retry_backoff: Backoff::default(),
+            max_retries: 0,
+            retry_delay: default_retry_delay(),
+            retry_max_delay: 0,
         }
     }
 }
@@ -115,6 +750,8 @@ fn default_url() -> Url {
     Url::parse("http://localhost:8080").unwrap()
 }
 
+fn default_rest_root() -> String { "engine-rest".to_string() }
+
 /// The default poll interval in milliseconds
 fn default_poll_interval() -> usize { 500 }
 
@@ -122,6 +759,78 @@ fn default_task_worker_id() -> String { "operaton_task_worker".to_string() }
 
 fn default_lock_duration() -> u64 { 60_000 }
 
+fn default_max_tasks() -> usize { 10 }
+
+fn default_connect_timeout() -> u64 { 10_000 }
+
+fn default_request_timeout() -> u64 { 30_000 }
+
+fn default_tcp_keepalive() -> u64 { 60_000 }
+
+fn default_pool_idle_timeout() -> u64 { 90_000 }
+
+fn default_pool_max_idle_per_host() -> usize { 10 }
+
+fn default_http_max_retries() -> u32 { 3 }
+
+fn default_http_retry_delay() -> u64 { 200 }
+
+fn default_http_retry_max_delay() -> u64 { 10_000 }
+
+fn default_outbox_retry_delay() -> u64 { 1_000 }
+
+fn default_outbox_retry_max_delay() -> u64 { 60_000 }
+
+fn default_deployment_source() -> String { "operaton-task-worker".to_string() }
+
+fn default_deploy_changed_only() -> bool { true }
+
+fn default_auto_extend_lock() -> bool { true }
+
+fn default_retry_delay() -> u64 { 10_000 }
+
+/// Deserialize a list of strings from a sequence or from a comma separated string, as environment variables provide it
+fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringList {
+        List(Vec<String>),
+        CommaSeparated(String),
+    }
+
+    Ok(match StringList::deserialize(deserializer)? {
+        StringList::List(list) => list,
+        StringList::CommaSeparated(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Deserialize the subscription filters from a map or from a JSON object, as environment variables provide it
+fn deserialize_subscription_filters<'de, D>(deserializer: D) -> Result<HashMap<String, SubscriptionFilter>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SubscriptionFilters {
+        Map(HashMap<String, SubscriptionFilter>),
+        Json(String),
+    }
+
+    match SubscriptionFilters::deserialize(deserializer)? {
+        SubscriptionFilters::Map(filters) => Ok(filters),
+        SubscriptionFilters::Json(json) if json.trim().is_empty() => Ok(HashMap::new()),
+        SubscriptionFilters::Json(json) => serde_json::from_str(&json).map_err(serde::de::Error::custom),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -143,9 +852,152 @@ mod test {
         assert_eq!(config.lock_duration(), 12_345);
     }
 
+    #[test]
+    fn test_fetching_and_connection_builders() {
+        let config = ConfigParams::default()
+            .with_max_tasks(5)
+            .with_use_priority(true)
+            .with_async_response_timeout(30_000)
+            .with_connect_timeout(1_000)
+            .with_request_timeout(2_000)
+            .with_tcp_keepalive(0)
+            .with_connection_pool(5_000, 2)
+            .with_auto_extend_lock(false)
+            .with_unhandled_task_policy(UnhandledTaskPolicy::Incident)
+            .with_retry_policy(RetryPolicy { backoff: Backoff::Exponential, max_retries: 3, delay: 1_000, max_delay: 60_000 });
+
+        assert_eq!(config.max_tasks(), 5);
+        assert!(config.use_priority());
+        assert_eq!(config.async_response_timeout(), 30_000);
+        assert!(config.long_polling());
+        assert_eq!(config.connect_timeout(), 1_000);
+        assert_eq!(config.request_timeout(), 2_000);
+        assert_eq!(config.tcp_keepalive(), 0);
+        assert_eq!(config.pool_idle_timeout(), 5_000);
+        assert_eq!(config.pool_max_idle_per_host(), 2);
+        assert!(!config.auto_extend_lock());
+        assert_eq!(config.unhandled_task_policy(), UnhandledTaskPolicy::Incident);
+        assert_eq!(
+            config.retry_policy(),
+            RetryPolicy { backoff: Backoff::Exponential, max_retries: 3, delay: 1_000, max_delay: 60_000 }
+        );
+    }
+
+    #[test]
+    fn test_oauth2_client_credentials() {
+        let config = ConfigParams::default().with_oauth2_client_credentials(
+            Url::parse("https://idp.example.com/token").unwrap(),
+            "worker".to_string(),
+            "secret".to_string(),
+            Some("operaton".to_string()),
+        );
+
+        assert!(config.has_authentication());
+        assert_eq!(config.oauth2_token_url(), Some(&Url::parse("https://idp.example.com/token").unwrap()));
+        assert_eq!(config.oauth2_client_id(), "worker");
+        assert_eq!(config.oauth2_client_secret(), "secret");
+        assert_eq!(config.oauth2_scope(), Some("operaton"));
+        assert!(!ConfigParams::default().has_authentication());
+    }
+
+    #[test]
+    fn test_debug_output_redacts_secrets() {
+        let config = ConfigParams::default()
+            .with_auth("user".to_string(), "basic-secret".to_string())
+            .with_oauth2_client_credentials(
+                Url::parse("https://idp.example.com/token").unwrap(),
+                "worker".to_string(),
+                "oauth2-secret".to_string(),
+                None,
+            );
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("\"user\""));
+        assert!(debug.contains("\"worker\""));
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains("basic-secret"));
+        assert!(!debug.contains("oauth2-secret"));
+    }
+
+    #[test]
+    fn test_deployment_name_defaults_to_worker_id() {
+        let config = ConfigParams::default().with_worker_id("invoice-worker".to_string());
+        assert_eq!(config.deployment_dir(), None);
+        assert_eq!(config.deployment_name(), "invoice-worker");
+
+        let config = config.with_deployment(Some(PathBuf::from("bpmn")), Some("invoice".to_string()));
+        assert_eq!(config.deployment_dir(), Some(Path::new("bpmn")));
+        assert_eq!(config.deployment_name(), "invoice");
+    }
+
     #[test]
     fn test_default_lock_duration() {
         let cfg = ConfigParams::default();
         assert_eq!(cfg.lock_duration(), default_lock_duration());
     }
+
+    #[test]
+    fn test_policies_from_strings() {
+        let config: ConfigParams = Config::builder()
+            .set_override("unhandled_task_policy", "failure")
+            .unwrap()
+            .set_override("retry_backoff", "linear")
+            .unwrap()
+            .set_override("max_retries", "4")
+            .unwrap()
+            .set_override("startup_check", "fail")
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.unhandled_task_policy(), UnhandledTaskPolicy::Failure);
+        assert_eq!(config.retry_policy().backoff, Backoff::Linear);
+        assert_eq!(config.retry_policy().max_retries, 4);
+        assert_eq!(config.startup_check(), StartupCheck::Fail);
+        assert_eq!(ConfigParams::default().unhandled_task_policy(), UnhandledTaskPolicy::Unlock);
+    }
+
+    #[test]
+    fn test_tenant_ids_from_comma_separated_string() {
+        let config: ConfigParams = Config::builder()
+            .set_override("tenant_id_in", "tenant-a, tenant-b")
+            .unwrap()
+            .set_override("without_tenant_id", "true")
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.tenant_id_in(), ["tenant-a".to_string(), "tenant-b".to_string()]);
+        assert!(config.without_tenant_id());
+        assert!(ConfigParams::default().tenant_id_in().is_empty());
+    }
+
+    #[test]
+    fn test_subscription_filters_from_json_string() {
+        let config: ConfigParams = Config::builder()
+            .set_override(
+                "subscription_filters",
+                r#"{"invoicing": {"process_definition_key_in": ["Invoice"], "process_variables": {"region": "EU"}}}"#,
+            )
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        let filter = config.subscription_filter("invoicing").unwrap();
+        assert_eq!(filter.process_definition_key_in, ["Invoice".to_string()]);
+        assert_eq!(filter.process_variables["region"], serde_json::json!("EU"));
+        assert_eq!(filter.business_key, None);
+        assert!(config.subscription_filter("stock").is_none());
+    }
+
+    #[test]
+    fn test_long_polling_disabled_by_default() {
+        let cfg = ConfigParams::default();
+        assert_eq!(cfg.async_response_timeout(), 0);
+        assert!(!cfg.long_polling());
+    }
 }
\ No newline at end of file
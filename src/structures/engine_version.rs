@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+/// The product of the engine behind the REST API
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineFlavor {
+    Operaton,
+    Camunda7,
+    Unknown,
+}
+
+impl std::fmt::Display for EngineFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineFlavor::Operaton => write!(f, "Operaton"),
+            EngineFlavor::Camunda7 => write!(f, "Camunda 7"),
+            EngineFlavor::Unknown => write!(f, "unknown engine"),
+        }
+    }
+}
+
+/// The version of the engine as returned by `GET /version`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EngineVersion {
+    /// The version string, e.g. `1.0.0` or `7.22.0-ee`
+    version: String,
+}
+
+impl EngineVersion {
+    pub fn new(version: impl Into<String>) -> Self {
+        Self { version: version.into() }
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The numeric major, minor and patch version. Missing parts are `0`; suffixes such as `-ee` are ignored.
+    pub fn numbers(&self) -> Option<(u64, u64, u64)> {
+        let core = self.version.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u64>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some((major, minor, patch))
+    }
+
+    /// The engine is derived from the version: Operaton started with version 1, Camunda 7 has major version 7
+    pub fn flavor(&self) -> EngineFlavor {
+        match self.numbers() {
+            Some((1, _, _)) => EngineFlavor::Operaton,
+            Some((7, _, _)) => EngineFlavor::Camunda7,
+            _ => EngineFlavor::Unknown,
+        }
+    }
+
+    /// Whether the crate works with this engine version: Operaton 1.x and Camunda 7.20 or later, which reports error codes
+    pub fn is_supported(&self) -> bool {
+        match self.numbers() {
+            Some((1, _, _)) => true,
+            Some((7, minor, _)) => minor >= 20,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for EngineVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.flavor(), self.version)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_engine_versions() {
+        let operaton: EngineVersion = serde_json::from_str(r#"{"version":"1.0.0-beta-4"}"#).unwrap();
+        assert_eq!(operaton.numbers(), Some((1, 0, 0)));
+        assert_eq!(operaton.flavor(), EngineFlavor::Operaton);
+        assert!(operaton.is_supported());
+
+        let camunda = EngineVersion::new("7.22.0-ee");
+        assert_eq!(camunda.flavor(), EngineFlavor::Camunda7);
+        assert!(camunda.is_supported());
+        assert!(!EngineVersion::new("7.17.0").is_supported());
+
+        let unknown = EngineVersion::new("snapshot");
+        assert_eq!(unknown.numbers(), None);
+        assert_eq!(unknown.flavor(), EngineFlavor::Unknown);
+        assert!(!unknown.is_supported());
+    }
+}
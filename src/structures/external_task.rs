@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// An external task as returned by the external task queries of the REST API
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalTask {
+    pub id: String,
+    pub topic_name: Option<String>,
+    pub worker_id: Option<String>,
+    /// The time until which the task is locked, `None` if it is not locked
+    pub lock_expiration_time: Option<String>,
+    /// The remaining retries, `None` if no failure has been reported yet
+    pub retries: Option<i32>,
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub suspended: bool,
+    pub priority: i64,
+    pub activity_id: Option<String>,
+    pub activity_instance_id: Option<String>,
+    pub execution_id: Option<String>,
+    pub process_instance_id: Option<String>,
+    pub process_definition_id: Option<String>,
+    pub process_definition_key: Option<String>,
+    #[serde(default)]
+    pub process_definition_version_tag: Option<String>,
+    #[serde(default)]
+    pub business_key: Option<String>,
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub create_time: Option<String>,
+}
+
+impl ExternalTask {
+    /// Whether the task is currently locked by a worker
+    pub fn is_locked(&self) -> bool {
+        self.lock_expiration_time.is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_external_tasks() {
+        let response_string = r#"[{"activityId":"ServiceTask_OrderPizza","activityInstanceId":"ServiceTask_OrderPizza:f9bb0ecb","errorMessage":null,"executionId":"f9bb0eca","id":"f9bb0ecc","lockExpirationTime":"2025-11-20T10:00:00.000+0000","processDefinitionId":"OrderPizza:3:f2d157ce","processDefinitionKey":"OrderPizza","processDefinitionVersionTag":null,"processInstanceId":"f2d4da42","tenantId":null,"retries":2,"suspended":false,"workerId":"operaton_task_worker","priority":10,"topicName":"ServiceTask_OrderPizza","businessKey":null,"createTime":"2025-11-20T09:55:00.000+0000"}]"#;
+        let tasks: Vec<ExternalTask> = serde_json::from_str(response_string).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].retries, Some(2));
+        assert_eq!(tasks[0].priority, 10);
+        assert!(tasks[0].is_locked());
+    }
+}
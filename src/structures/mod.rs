@@ -1,3 +1,5 @@
+pub mod engine_version;
+pub mod external_task;
 pub mod process_variables;
 pub mod service_task;
 
@@ -1,48 +1,143 @@
 use std::collections::HashMap;
+use std::fmt;
+use base64::Engine as _;
+use chrono::{DateTime, FixedOffset};
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 
+use crate::variables::{VariableError, VariableProblem, VariablesError};
+
+/// The format of Date variables in the REST API
+pub(crate) const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%z";
+
+/// The serialization data format of JSON documents
+const JSON_DATA_FORMAT: &str = "application/json";
+
+/// A Json variable
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct JsonValue {
-    data_format_name: String,
+pub struct JsonVar {
+    /// The JSON document
+    pub value: serde_json::Value,
 
-    value: serde_json::Value,
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoolVar {
+    pub value: bool,
+
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
 
-    string: bool,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StringVar {
+    pub value: String,
 
-    object: bool,
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
 
-    boolean: bool,
+/// A File variable. The content is not part of the variable and is downloaded on demand with
+/// [crate::context::TaskContext::variable_data].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileVar {
+    pub filename: Option<String>,
 
-    number: bool,
+    pub mime_type: Option<String>,
 
-    array: bool,
+    pub encoding: Option<String>,
 
-    #[serde(rename = "null")]
-    null_val: bool,
+    /// The id of the variable instance, if the engine returned it
+    pub id: Option<String>,
 
-    node_type: String,
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
 }
 
+/// A Bytes variable. The engine may omit the content, which is then downloaded on demand with
+/// [crate::context::TaskContext::variable_data].
 #[derive(Debug, Serialize, Deserialize)]
-pub struct JsonVar {
-    #[serde(rename = "value")]
-    pub json_value: JsonValue,
+pub struct BytesVar {
+    pub value: Option<Vec<u8>>,
+
+    /// The id of the variable instance, if the engine returned it
+    pub id: Option<String>,
 
     #[serde(rename = "valueInfo")]
     pub value_info: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct BoolVar {
-    pub value: bool,
+pub struct IntegerVar {
+    pub value: i32,
 
     #[serde(rename = "valueInfo")]
     pub value_info: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct StringVar {
+pub struct LongVar {
+    pub value: i64,
+
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortVar {
+    pub value: i16,
+
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DoubleVar {
+    pub value: f64,
+
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+/// A Date variable. The engine sends dates as `yyyy-MM-dd'T'HH:mm:ss.SSSZ`, e.g. `2025-11-20T10:00:00.000+0100`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DateVar {
+    pub value: DateTime<FixedOffset>,
+
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NullVar {
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+/// An Object variable, i.e. a Java object serialized by the engine
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ObjectVar {
+    /// The serialized object, usually a string in the serialization data format
+    pub value: serde_json::Value,
+
+    /// The object as JSON document, if it is serialized as `application/json`
+    pub json: Option<serde_json::Value>,
+
+    /// The Java class of the object, e.g. `java.util.ArrayList<java.lang.String>`
+    pub object_type_name: Option<String>,
+
+    /// The format of the serialized object, e.g. `application/json` or `application/x-java-serialized-object`
+    pub serialization_data_format: Option<String>,
+
+    #[serde(rename = "valueInfo")]
+    pub value_info: HashMap<String, serde_json::Value>,
+}
+
+/// A Spin XML variable
+#[derive(Debug, Serialize, Deserialize)]
+pub struct XmlVar {
     pub value: String,
 
     #[serde(rename = "valueInfo")]
@@ -54,9 +149,37 @@ pub enum ProcessInstanceVariable {
     Json(JsonVar),
     Boolean(BoolVar),
     String(StringVar),
+    File(FileVar),
+    Bytes(BytesVar),
+    Integer(IntegerVar),
+    Long(LongVar),
+    Short(ShortVar),
+    Double(DoubleVar),
+    Date(DateVar),
+    Null(NullVar),
+    Object(ObjectVar),
+    Xml(XmlVar),
 }
 
 impl ProcessInstanceVariable {
+    /// The type of the variable as named by the engine
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ProcessInstanceVariable::Json(_) => "Json",
+            ProcessInstanceVariable::Boolean(_) => "Boolean",
+            ProcessInstanceVariable::String(_) => "String",
+            ProcessInstanceVariable::File(_) => "File",
+            ProcessInstanceVariable::Bytes(_) => "Bytes",
+            ProcessInstanceVariable::Integer(_) => "Integer",
+            ProcessInstanceVariable::Long(_) => "Long",
+            ProcessInstanceVariable::Short(_) => "Short",
+            ProcessInstanceVariable::Double(_) => "Double",
+            ProcessInstanceVariable::Date(_) => "Date",
+            ProcessInstanceVariable::Null(_) => "Null",
+            ProcessInstanceVariable::Object(_) => "Object",
+            ProcessInstanceVariable::Xml(_) => "Xml",
+        }
+    }
     pub fn as_bool(&self) -> Option<bool> {
         match self {
             ProcessInstanceVariable::Boolean(b) => Some(b.value),
@@ -69,16 +192,88 @@ impl ProcessInstanceVariable {
             _ => None,
         }
     }
+    /// The value of an Integer or Short variable
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            ProcessInstanceVariable::Integer(i) => Some(i.value),
+            ProcessInstanceVariable::Short(s) => Some(s.value.into()),
+            _ => None,
+        }
+    }
+    /// The value of a Long, Integer or Short variable
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ProcessInstanceVariable::Long(l) => Some(l.value),
+            ProcessInstanceVariable::Integer(i) => Some(i.value.into()),
+            ProcessInstanceVariable::Short(s) => Some(s.value.into()),
+            _ => None,
+        }
+    }
+    /// The value of a Double variable, or of an Integer, Short or Long variable converted to `f64`
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ProcessInstanceVariable::Double(d) => Some(d.value),
+            ProcessInstanceVariable::Long(l) => Some(l.value as f64),
+            ProcessInstanceVariable::Integer(i) => Some(i.value.into()),
+            ProcessInstanceVariable::Short(s) => Some(s.value.into()),
+            _ => None,
+        }
+    }
+    pub fn as_date(&self) -> Option<DateTime<FixedOffset>> {
+        match self {
+            ProcessInstanceVariable::Date(d) => Some(d.value),
+            _ => None,
+        }
+    }
+    pub fn as_object(&self) -> Option<&ObjectVar> {
+        match self {
+            ProcessInstanceVariable::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+    /// The XML document of a Spin XML variable
+    pub fn as_xml(&self) -> Option<&str> {
+        match self {
+            ProcessInstanceVariable::Xml(x) => Some(&x.value),
+            _ => None,
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self, ProcessInstanceVariable::Null(_))
+    }
+    /// The JSON document of a Json variable or of an Object variable serialized as `application/json`
     pub fn as_json(&self) -> Option<&serde_json::Value> {
         match self {
-            ProcessInstanceVariable::Json(j) => Some(&j.json_value.value),
+            ProcessInstanceVariable::Json(j) => Some(&j.value),
+            ProcessInstanceVariable::Object(o) => o.json.as_ref(),
+            _ => None,
+        }
+    }
+    pub fn as_file(&self) -> Option<&FileVar> {
+        match self {
+            ProcessInstanceVariable::File(f) => Some(f),
+            _ => None,
+        }
+    }
+    /// The content of a Bytes variable, `None` if the engine did not include it
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            ProcessInstanceVariable::Bytes(b) => b.value.as_deref(),
+            _ => None,
+        }
+    }
+    /// The id of the variable instance of a File or Bytes variable, which is needed to download its content
+    pub fn variable_instance_id(&self) -> Option<&str> {
+        match self {
+            ProcessInstanceVariable::File(f) => f.id.as_deref(),
+            ProcessInstanceVariable::Bytes(b) => b.id.as_deref(),
             _ => None,
         }
     }
 }
 
 /// This represents an entry of the original JSON
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Entry {
     #[serde(rename = "type")]
     typ: String,
@@ -87,12 +282,148 @@ pub struct Entry {
     #[allow(dead_code)]
     name: String,
 
+    /// The id of the variable instance, only returned by the variable instance queries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+
+    #[serde(default)]
     value: serde_json::Value,
 
-    #[serde(rename = "valueInfo")]
+    #[serde(rename = "valueInfo", default)]
     value_info: HashMap<String, serde_json::Value>,
 }
 
+fn value_info_string(value_info: &HashMap<String, serde_json::Value>, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| value_info.get(*key).and_then(|value| value.as_str()).map(str::to_string))
+}
+
+impl Entry {
+    /// Whether this is a Json or JSON serialized Object variable of which the engine sent only the metadata of its
+    /// JSON node, so its document has to be fetched separately
+    pub(crate) fn lacks_json_document(&self) -> bool {
+        let is_json = match self.typ.as_str() {
+            "Json" => true,
+            "Object" => value_info_string(&self.value_info, &["serializationDataFormat"]).as_deref() == Some(JSON_DATA_FORMAT),
+            _ => false,
+        };
+        is_json && is_json_node_metadata(&self.value)
+    }
+
+    /// Replace the value, e.g. with the serialized document fetched for [Entry::lacks_json_document]
+    pub(crate) fn set_value(&mut self, value: serde_json::Value) {
+        self.value = value;
+    }
+
+    fn into_file_var(self) -> FileVar {
+        FileVar {
+            filename: value_info_string(&self.value_info, &["filename"]),
+            // The engine writes `mimeType`, but the REST API documentation uses `mimetype`
+            mime_type: value_info_string(&self.value_info, &["mimeType", "mimetype"]),
+            encoding: value_info_string(&self.value_info, &["encoding"]),
+            id: self.id,
+            value_info: self.value_info,
+        }
+    }
+
+    fn into_bytes_var(self) -> Result<BytesVar, String> {
+        // The content is Base64 encoded if the engine includes it
+        let value = match self.value.as_str() {
+            Some(encoded) => Some(
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .map_err(|err| format!("invalid Base64 content of a Bytes variable: {}", err))?,
+            ),
+            None => None,
+        };
+        Ok(BytesVar { value, id: self.id, value_info: self.value_info })
+    }
+}
+
+/// Parse a date as sent by the engine, falling back to RFC 3339
+pub(crate) fn parse_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(value, DATE_FORMAT)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+}
+
+/// Whether a value is the metadata of a Spin JSON node (`dataFormatName`, `nodeType`, ...) without the document.
+/// A `NULL` node has no document to miss.
+fn is_json_node_metadata(value: &serde_json::Value) -> bool {
+    value.as_object().is_some_and(|node| {
+        node.contains_key("dataFormatName")
+            && node.get("nodeType").is_some_and(|node_type| node_type.as_str() != Some("NULL"))
+    })
+}
+
+/// The JSON document of a Json or JSON serialized Object variable.
+///
+/// Depending on the endpoint and `deserializeValues`, the engine returns the document as serialized string
+/// (e.g. fetchAndLock and `deserializeValues=false`), as JSON value, or only the metadata of the Spin JSON node
+/// (`dataFormatName`, `nodeType`, ...) without the document. The worker fetches the document of the latter before the
+/// variables are parsed, so the metadata only remains if that failed.
+fn json_document(value: serde_json::Value) -> Result<serde_json::Value, String> {
+    if is_json_node_metadata(&value) {
+        return Err("the engine returned the metadata of the JSON node instead of the document".to_string());
+    }
+    match value {
+        serde_json::Value::String(serialized) => {
+            serde_json::from_str(&serialized).map_err(|err| format!("invalid JSON document: {}", err))
+        }
+        // The metadata of a NULL node is all there is to its document
+        serde_json::Value::Object(node) if node.contains_key("dataFormatName") && node.contains_key("nodeType") => {
+            Ok(serde_json::Value::Null)
+        }
+        value => Ok(value),
+    }
+}
+
+impl TryFrom<Entry> for ProcessInstanceVariable {
+    type Error = String;
+
+    fn try_from(entry: Entry) -> Result<Self, Self::Error> {
+        fn value<T: serde::de::DeserializeOwned>(entry: &mut Entry) -> Result<T, String> {
+            serde_json::from_value(entry.value.take())
+                .map_err(|err| format!("invalid value for a {} variable: {}", entry.typ, err))
+        }
+
+        let mut entry = entry;
+        let variable = match entry.typ.as_str() {
+            "Json" => ProcessInstanceVariable::Json(JsonVar { value: json_document(entry.value)?, value_info: entry.value_info }),
+            "Boolean" => ProcessInstanceVariable::Boolean(BoolVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            "String" => ProcessInstanceVariable::String(StringVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            "File" => ProcessInstanceVariable::File(entry.into_file_var()),
+            "Bytes" => ProcessInstanceVariable::Bytes(entry.into_bytes_var()?),
+            "Integer" => ProcessInstanceVariable::Integer(IntegerVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            "Long" => ProcessInstanceVariable::Long(LongVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            "Short" => ProcessInstanceVariable::Short(ShortVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            "Double" => ProcessInstanceVariable::Double(DoubleVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            "Date" => {
+                let date: String = value(&mut entry)?;
+                let date = parse_date(&date).ok_or_else(|| format!("invalid value for a Date variable: {}", date))?;
+                ProcessInstanceVariable::Date(DateVar { value: date, value_info: entry.value_info })
+            }
+            "Null" => ProcessInstanceVariable::Null(NullVar { value_info: entry.value_info }),
+            "Object" => {
+                let serialization_data_format = value_info_string(&entry.value_info, &["serializationDataFormat"]);
+                let json = match serialization_data_format.as_deref() {
+                    Some(JSON_DATA_FORMAT) => Some(json_document(entry.value.clone())?),
+                    _ => None,
+                };
+                ProcessInstanceVariable::Object(ObjectVar {
+                    value: entry.value,
+                    json,
+                    object_type_name: value_info_string(&entry.value_info, &["objectTypeName"]),
+                    serialization_data_format,
+                    value_info: entry.value_info,
+                })
+            }
+            "Xml" => ProcessInstanceVariable::Xml(XmlVar { value: value(&mut entry)?, value_info: entry.value_info }),
+            _ => return Err(format!("unknown type: {}", entry.typ)),
+        };
+        Ok(variable)
+    }
+}
+
 impl<'de> Deserialize<'de> for ProcessInstanceVariable {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -102,161 +433,192 @@ impl<'de> Deserialize<'de> for ProcessInstanceVariable {
 
         // We expect only one entry in practice, but we'll take the first valid one
         // Or collect all into Vec<Var> if you want multiple
-        for (_, entry) in map {
-            return match entry.typ.as_str() {
-                "Json" => {
-                    let json_var = JsonVar {
-                        json_value: serde_json::from_value(entry.value).map_err(serde::de::Error::custom)?,
-                        value_info: entry.value_info,
-                    };
-                    Ok(ProcessInstanceVariable::Json(json_var))
-                }
-                "Boolean" => {
-                    let bool_var = BoolVar {
-                        value: serde_json::from_value(entry.value).map_err(serde::de::Error::custom)?,
-                        value_info: entry.value_info,
-                    };
-                    Ok(ProcessInstanceVariable::Boolean(bool_var))
-                },
-                "String" => {
-                    let string_var = StringVar {
-                        value: serde_json::from_value(entry.value).map_err(serde::de::Error::custom)?,
-                        value_info: entry.value_info,
-                    };
-                    Ok(ProcessInstanceVariable::String(string_var))
-                },
-                _ => Err(serde::de::Error::custom(format!("unknown type: {}", entry.typ))),
-            };
+        if let Some((_, entry)) = map.into_iter().next() {
+            return ProcessInstanceVariable::try_from(entry).map_err(serde::de::Error::custom);
         }
 
         Err(serde::de::Error::custom("no valid entries found"))
     }
 }
 
+/// The error of [try_parse_process_instance_variables]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseVariablesError {
+    /// The document is neither a map nor a list of variables
+    InvalidDocument(String),
+    /// The document is valid, but some of its variables could not be parsed
+    InvalidVariables(VariablesError),
+}
+
+impl fmt::Display for ParseVariablesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseVariablesError::InvalidDocument(message) => write!(f, "Invalid variables document: {}", message),
+            ParseVariablesError::InvalidVariables(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseVariablesError {}
+
+/// The variables which could be parsed, and a diagnostic for every variable which could not
+#[derive(Debug, Default)]
+struct ParsedVariables {
+    variables: HashMap<String, ProcessInstanceVariable>,
+    errors: Vec<VariableError>,
+}
+
+impl ParsedVariables {
+    fn insert(&mut self, name: String, entry: Entry) {
+        match ProcessInstanceVariable::try_from(entry) {
+            Ok(variable) => {
+                self.variables.insert(name, variable);
+            }
+            Err(message) => self.errors.push(VariableError { name, problem: VariableProblem::Invalid(message) }),
+        }
+    }
+
+    fn into_result(self) -> Result<HashMap<String, ProcessInstanceVariable>, VariablesError> {
+        if self.errors.is_empty() {
+            Ok(self.variables)
+        } else {
+            Err(VariablesError { errors: self.errors })
+        }
+    }
+
+    /// Log the diagnostics and keep the variables which could be parsed
+    fn into_variables_with_warnings(self) -> HashMap<String, ProcessInstanceVariable> {
+        for error in &self.errors {
+            warn!("Ignoring the variable {}", error);
+        }
+        self.variables
+    }
+}
+
+fn parse_entries(entries: HashMap<String, Entry>) -> ParsedVariables {
+    let mut parsed = ParsedVariables::default();
+    for (name, entry) in entries {
+        parsed.insert(name, entry);
+    }
+    parsed
+}
+
+/// Convert the variables of a fetched external task (a map of name -> `{ type, value, valueInfo }`).
+/// Variables which cannot be parsed are logged and skipped.
+pub fn parse_variable_entries(entries: HashMap<String, Entry>) -> HashMap<String, ProcessInstanceVariable> {
+    parse_entries(entries).into_variables_with_warnings()
+}
+
+/// Convert the variables of a fetched external task, failing with a diagnostic for every variable which cannot be parsed
+pub fn try_parse_variable_entries(entries: HashMap<String, Entry>) -> Result<HashMap<String, ProcessInstanceVariable>, VariablesError> {
+    parse_entries(entries).into_result()
+}
+
+/// Parse the variables returned by the REST API. Variables which cannot be parsed are logged and skipped,
+/// an invalid document results in no variables.
 pub fn parse_process_instance_variables(json_str: &str) -> HashMap<String, ProcessInstanceVariable> {
+    match parse_document(json_str) {
+        Ok(parsed) => parsed.into_variables_with_warnings(),
+        Err(message) => {
+            warn!("Ignoring the variables document, which cannot be parsed: {}", message);
+            HashMap::new()
+        }
+    }
+}
+
+/// Parse the variables returned by the REST API, failing with a diagnostic for every variable which cannot be parsed
+pub fn try_parse_process_instance_variables(json_str: &str) -> Result<HashMap<String, ProcessInstanceVariable>, ParseVariablesError> {
+    parse_document(json_str)
+        .map_err(ParseVariablesError::InvalidDocument)?
+        .into_result()
+        .map_err(ParseVariablesError::InvalidVariables)
+}
+
+fn parse_document(json_str: &str) -> Result<ParsedVariables, String> {
     // According to Camunda 7/Operaton, the variable endpoint usually returns an object map of
     // name -> { type, value, valueInfo }. However, sometimes multiple JSON values can be returned
-    // as a JSON sequence (concatenated JSON values or an array). This function now handles:
+    // as a JSON sequence (concatenated JSON values or an array). This function handles:
     // 1) A single JSON object map
     // 2) A JSON array of such maps
     // 3) A JSON array of entries (flat list with `name` inside)
     // 4) A concatenated JSON sequence of such maps or entries
 
-    // Helper to convert an Entry into our enum and insert into result map
-    fn insert_entry(result: &mut HashMap<String, ProcessInstanceVariable>, name: String, entry: Entry) {
-        let parsed_var = match entry.typ.as_str() {
-            "Json" => ProcessInstanceVariable::Json(JsonVar {
-                json_value: serde_json::from_value(entry.value).unwrap_or_else(|e| {
-                    println!("Failed to parse JsonVar value for {}: {:#?}", name, e);
-                    // Fallback empty JSON details
-                    JsonValue {
-                        data_format_name: String::new(),
-                        value: serde_json::Value::Null,
-                        string: false,
-                        object: false,
-                        boolean: false,
-                        number: false,
-                        array: false,
-                        null_val: true,
-                        node_type: String::new(),
-                    }
-                }),
-                value_info: entry.value_info,
-            }),
-            "Boolean" => ProcessInstanceVariable::Boolean(BoolVar {
-                value: serde_json::from_value(entry.value).unwrap_or(false),
-                value_info: entry.value_info,
-            }),
-            "String" => ProcessInstanceVariable::String(StringVar {
-                value: serde_json::from_value(entry.value).unwrap_or_default(),
-                value_info: entry.value_info,
-            }),
-            _ => return,
-        };
-        result.insert(name, parsed_var);
-    }
-
-    let mut result: HashMap<String, ProcessInstanceVariable> = HashMap::new();
-
     // Strategy 1: Try a single object map
     if let Ok(parsed_map) = serde_json::from_str::<HashMap<String, Entry>>(json_str) {
-        for (name, entry) in parsed_map {
-            insert_entry(&mut result, name, entry);
-        }
-        return result;
+        return Ok(parse_entries(parsed_map));
     }
 
+    let mut parsed = ParsedVariables::default();
+
     // Strategy 2: Try an array of entries (flat list with `name` field)
     if let Ok(entries) = serde_json::from_str::<Vec<Entry>>(json_str) {
         for entry in entries.into_iter() {
-            let name = if !entry.name.is_empty() { entry.name.clone() } else { continue };
-            insert_entry(&mut result, name, entry);
+            if entry.name.is_empty() {
+                debug!("Skipping a variable without name in a variable list");
+                continue;
+            }
+            parsed.insert(entry.name.clone(), entry);
         }
-        return result;
+        return Ok(parsed);
     }
 
     // Strategy 3: Try an array of object maps
     if let Ok(parsed_vec) = serde_json::from_str::<Vec<HashMap<String, Entry>>>(json_str) {
         for map in parsed_vec.into_iter() {
             for (name, entry) in map {
-                insert_entry(&mut result, name, entry);
+                parsed.insert(name, entry);
             }
         }
-        return result;
+        return Ok(parsed);
     }
 
     // Strategy 4a: Stream/sequence of concatenated Entry values
-    let deser_entries = serde_json::Deserializer::from_str(json_str);
-    let mut stream_entries = deser_entries.into_iter::<Entry>();
     let mut any_parsed = false;
-    while let Some(next) = stream_entries.next() {
+    for next in serde_json::Deserializer::from_str(json_str).into_iter::<Entry>() {
         match next {
-            Ok(entry) => {
-                if !entry.name.is_empty() {
-                    let name = entry.name.clone();
-                    insert_entry(&mut result, name, entry);
-                    any_parsed = true;
-                }
+            Ok(entry) if !entry.name.is_empty() => {
+                parsed.insert(entry.name.clone(), entry);
+                any_parsed = true;
             }
-            Err(e) => {
-                println!("Error while parsing JSON Entry sequence chunk: {:#?}", e);
+            Ok(_) => {}
+            Err(err) => {
+                debug!("Stop parsing the variables as sequence of entries: {}", err);
                 break;
             }
         }
     }
     if any_parsed {
-        return result;
+        return Ok(parsed);
     }
 
     // Strategy 4b: Stream/sequence of concatenated map values
-    let deser_maps = serde_json::Deserializer::from_str(json_str);
-    let mut stream_maps = deser_maps.into_iter::<HashMap<String, Entry>>();
-    while let Some(next) = stream_maps.next() {
+    let mut last_error = None;
+    for next in serde_json::Deserializer::from_str(json_str).into_iter::<HashMap<String, Entry>>() {
         match next {
             Ok(map) => {
                 any_parsed = true;
                 for (name, entry) in map {
-                    insert_entry(&mut result, name, entry);
+                    parsed.insert(name, entry);
                 }
             }
-            Err(e) => {
-                // Stop streaming on error; we will report below if nothing parsed
-                println!("Error while parsing JSON map sequence chunk: {:#?}", e);
+            Err(err) => {
+                // Stop streaming on error; the error is reported if nothing was parsed
+                last_error = Some(err.to_string());
                 break;
             }
         }
     }
 
-    if !any_parsed {
-        println!("Error while parsing \"{}\", ignoring it for now.", json_str);
+    if any_parsed {
+        Ok(parsed)
+    } else {
+        Err(last_error.unwrap_or_else(|| "the document contains no variables".to_string()))
     }
-
-    result
 }
 
 #[cfg(test)]
 mod test {
-    use crate::structures::process_variables::parse_process_instance_variables;
+    use super::*;
 
     #[test]
     fn test_module_parsing() {
@@ -275,8 +637,122 @@ mod test {
 
     }
 
+    #[test]
+    fn test_file_and_bytes_variables() {
+        let response_string: &str = r#"{"invoice":{"type":"File","value":null,"valueInfo":{"filename":"invoice.pdf","mimeType":"application/pdf","encoding":"UTF-8"}},"signature":{"type":"Bytes","value":"AQID","valueInfo":{}}}"#;
+        let variables = parse_process_instance_variables(response_string);
+
+        let file = variables["invoice"].as_file().unwrap();
+        assert_eq!(file.filename.as_deref(), Some("invoice.pdf"));
+        assert_eq!(file.mime_type.as_deref(), Some("application/pdf"));
+        assert_eq!(file.encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(variables["signature"].as_bytes(), Some([1u8, 2, 3].as_slice()));
+    }
+
+    #[test]
+    fn test_primitive_object_and_xml_variables() {
+        let response_string: &str = r#"{
+            "amount": {"type":"Integer","value":42,"valueInfo":{}},
+            "total": {"type":"Long","value":9007199254740993,"valueInfo":{}},
+            "count": {"type":"Short","value":7,"valueInfo":{}},
+            "rate": {"type":"Double","value":0.19,"valueInfo":{}},
+            "dueDate": {"type":"Date","value":"2025-11-20T10:00:00.000+0100","valueInfo":{}},
+            "approver": {"type":"Null","value":null,"valueInfo":{}},
+            "items": {"type":"Object","value":"[\"a\",\"b\"]","valueInfo":{"objectTypeName":"java.util.ArrayList","serializationDataFormat":"application/json"}},
+            "order": {"type":"Xml","value":"<order id=\"4711\"/>","valueInfo":{}},
+            "unknown": {"type":"SomethingElse","value":1,"valueInfo":{}}
+        }"#;
+        let variables = parse_process_instance_variables(response_string);
+
+        assert_eq!(variables["amount"].as_i32(), Some(42));
+        assert_eq!(variables["amount"].as_f64(), Some(42.0));
+        assert_eq!(variables["total"].as_i64(), Some(9_007_199_254_740_993));
+        assert_eq!(variables["total"].as_i32(), None);
+        assert_eq!(variables["count"].as_i64(), Some(7));
+        assert_eq!(variables["rate"].as_f64(), Some(0.19));
+        assert_eq!(
+            variables["dueDate"].as_date(),
+            Some(DateTime::parse_from_rfc3339("2025-11-20T10:00:00+01:00").unwrap())
+        );
+        assert!(variables["approver"].is_null());
+        let items = variables["items"].as_object().unwrap();
+        assert_eq!(items.object_type_name.as_deref(), Some("java.util.ArrayList"));
+        assert_eq!(items.serialization_data_format.as_deref(), Some("application/json"));
+        assert_eq!(variables["order"].as_xml(), Some("<order id=\"4711\"/>"));
+        assert!(!variables.contains_key("unknown"));
+    }
+
+    #[test]
+    fn test_json_document_regardless_of_serialization() {
+        let response_string: &str = r#"{
+            "serialized": {"type":"Json","value":"{\"sku\":\"A-1\",\"tags\":[\"new\"]}","valueInfo":{}},
+            "deserialized": {"type":"Json","value":{"sku":"A-1","tags":["new"]},"valueInfo":{}},
+            "empty": {"type":"Json","value":{"dataFormatName":"application/json","value":false,"string":false,"object":false,"boolean":false,"number":false,"array":false,"null":true,"nodeType":"NULL"},"valueInfo":{}},
+            "metadata": {"type":"Json","value":{"dataFormatName":"application/json","value":false,"string":false,"object":false,"boolean":false,"number":false,"array":true,"null":false,"nodeType":"ARRAY"},"valueInfo":{}},
+            "order": {"type":"Object","value":"{\"id\":4711}","valueInfo":{"objectTypeName":"com.example.Order","serializationDataFormat":"application/json"}},
+            "customer": {"type":"Object","value":{"id":42},"valueInfo":{"objectTypeName":"com.example.Customer","serializationDataFormat":"application/json"}},
+            "legacy": {"type":"Object","value":"rO0ABXQABHRlc3Q=","valueInfo":{"objectTypeName":"java.lang.String","serializationDataFormat":"application/x-java-serialized-object"}}
+        }"#;
+        let document = serde_json::json!({ "sku": "A-1", "tags": ["new"] });
+
+        let variables = parse_process_instance_variables(response_string);
+        assert_eq!(variables["serialized"].as_json(), Some(&document));
+        assert_eq!(variables["deserialized"].as_json(), Some(&document));
+        assert_eq!(variables["empty"].as_json(), Some(&serde_json::Value::Null));
+        assert!(!variables.contains_key("metadata"));
+        assert_eq!(variables["order"].as_json(), Some(&serde_json::json!({ "id": 4711 })));
+        assert_eq!(variables["customer"].as_json(), Some(&serde_json::json!({ "id": 42 })));
+        assert_eq!(variables["legacy"].as_json(), None);
+
+        // The metadata of a JSON node is reported instead of being taken as document
+        let Err(ParseVariablesError::InvalidVariables(err)) = try_parse_process_instance_variables(response_string) else {
+            panic!("the metadata should be invalid");
+        };
+        assert_eq!(err.errors.len(), 1);
+        assert_eq!(err.errors[0].name, "metadata");
+
+        let invalid = r#"{"broken": {"type":"Json","value":"{\"sku\":","valueInfo":{}}}"#;
+        assert!(try_parse_process_instance_variables(invalid).is_err());
+    }
+
+    #[test]
+    fn test_date_round_trip() {
+        let date = DateTime::parse_from_rfc3339("2025-11-20T10:00:00.250+01:00").unwrap();
+        let entry: Entry = serde_json::from_value(serde_json::to_value(crate::types::out_date(&date)).unwrap()).unwrap();
+        assert_eq!(ProcessInstanceVariable::try_from(entry).unwrap().as_date(), Some(date));
+    }
+
+    #[test]
+    fn test_strict_parsing_reports_every_variable() {
+        let response_string: &str = r#"{
+            "approved": {"type":"Boolean","value":"yes","valueInfo":{}},
+            "amount": {"type":"Integer","value":12,"valueInfo":{}},
+            "signature": {"type":"Bytes","value":"not base64!","valueInfo":{}},
+            "custom": {"type":"Custom","value":1,"valueInfo":{}}
+        }"#;
+
+        let Err(ParseVariablesError::InvalidVariables(err)) = try_parse_process_instance_variables(response_string) else {
+            panic!("the variables should be invalid");
+        };
+        let mut names: Vec<&str> = err.errors.iter().map(|error| error.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["approved", "custom", "signature"]);
+
+        // Without strict parsing, the valid variables are kept
+        let variables = parse_process_instance_variables(response_string);
+        assert_eq!(variables.len(), 1);
+        assert_eq!(variables["amount"].as_i32(), Some(12));
+
+        assert!(matches!(
+            try_parse_process_instance_variables("{\"invalid\":}"),
+            Err(ParseVariablesError::InvalidDocument(_))
+        ));
+    }
+
     #[test]
     fn test_module_parsing_invalid() {
         let response_string: &str = "{\"invalid\":}";
+        let variables = parse_process_instance_variables(response_string);
+        assert!(variables.is_empty());
     }
 }
\ No newline at end of file
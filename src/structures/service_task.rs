@@ -1,5 +1,11 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::structures::process_variables::{parse_variable_entries, try_parse_variable_entries, Entry};
+use crate::types::InputVariables;
+use crate::variables::VariablesError;
+
 /// An Operaton Service Task with its description elements
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -8,12 +14,33 @@ pub struct ServiceTask {
     id: String,
     /// The id of the Service Task (called `activityId` in Operaton)
     activity_id: String,
+    /// The activity instance of the task, the innermost scope of the variables visible to it
+    #[serde(default)]
+    activity_instance_id: Option<String>,
     process_instance_id: String,
+    #[serde(default)]
+    process_definition_id: Option<String>,
+    #[serde(default)]
+    process_definition_key: Option<String>,
+    #[serde(default)]
+    process_definition_version_tag: Option<String>,
+    #[serde(default)]
+    execution_id: Option<String>,
+    /// Not part of the fetchAndLock response, therefore optional
+    #[serde(default)]
     suspended: bool,
     topic_name: String,
-    priority: usize,
+    priority: i64,
     business_key: Option<String>,
     worker_id: Option<String>,
+    #[serde(default)]
+    tenant_id: Option<String>,
+    /// The remaining retries, `None` if no failure has been reported yet
+    #[serde(default)]
+    retries: Option<i32>,
+    /// The variables visible at the task's execution, as requested with the fetchAndLock call
+    #[serde(default)]
+    variables: HashMap<String, Entry>,
 }
 
 impl ServiceTask {
@@ -23,10 +50,30 @@ impl ServiceTask {
         &self.activity_id
     }
 
+    pub fn activity_instance_id(&self) -> Option<&str> {
+        self.activity_instance_id.as_deref()
+    }
+
     pub fn process_instance_id(&self) -> &str {
         &self.process_instance_id
     }
 
+    pub fn process_definition_id(&self) -> Option<&str> {
+        self.process_definition_id.as_deref()
+    }
+
+    pub fn process_definition_key(&self) -> Option<&str> {
+        self.process_definition_key.as_deref()
+    }
+
+    pub fn process_definition_version_tag(&self) -> Option<&str> {
+        self.process_definition_version_tag.as_deref()
+    }
+
+    pub fn execution_id(&self) -> Option<&str> {
+        self.execution_id.as_deref()
+    }
+
     pub fn suspended(&self) -> bool {
         self.suspended
     }
@@ -35,11 +82,73 @@ impl ServiceTask {
         &self.topic_name
     }
 
-    pub fn priority(&self) -> usize {
+    pub fn priority(&self) -> i64 {
         self.priority
     }
 
     pub fn business_key(&self) -> Option<String> {
         self.business_key.clone()
     }
-}
\ No newline at end of file
+
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.tenant_id.as_deref()
+    }
+
+    pub fn retries(&self) -> Option<i32> {
+        self.retries
+    }
+
+    /// The variables fetched with the task. Variables which cannot be parsed are logged and skipped.
+    pub fn variables(&self) -> InputVariables {
+        parse_variable_entries(self.variables.clone())
+    }
+
+    /// The variables fetched with the task, failing with a diagnostic for every variable which cannot be parsed
+    pub fn try_variables(&self) -> Result<InputVariables, VariablesError> {
+        try_parse_variable_entries(self.variables.clone())
+    }
+
+    /// The names of the Json and Object variables of which the engine sent only the metadata of their JSON node
+    pub(crate) fn variables_without_json_document(&self) -> Vec<String> {
+        self.variables.iter().filter(|(_, entry)| entry.lacks_json_document()).map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Replace the value of a fetched variable, e.g. with the document of a Json variable fetched separately
+    pub(crate) fn set_variable_value(&mut self, name: &str, value: serde_json::Value) {
+        if let Some(entry) = self.variables.get_mut(name) {
+            entry.set_value(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_fetch_and_lock_response() {
+        let response_string = r#"[{"activityId":"ServiceTask_OrderPizza","activityInstanceId":"ServiceTask_OrderPizza:f9bb0ecb","errorMessage":null,"errorDetails":null,"executionId":"f9bb0eca","id":"f9bb0ecc","lockExpirationTime":"2025-11-20T10:00:00.000+0000","processDefinitionId":"OrderPizza:3:f2d157ce","processDefinitionKey":"OrderPizza","processDefinitionVersionTag":null,"processInstanceId":"f2d4da42","retries":null,"workerId":"operaton_task_worker","priority":0,"topicName":"ServiceTask_OrderPizza","businessKey":null,"tenantId":null,"variables":{"pizza_wishlist":{"type":"String","value":"5x Vier Jahreszeiten","valueInfo":{}},"mehrheit_will_pizza":{"type":"Boolean","value":true,"valueInfo":{}}}}]"#;
+        let service_tasks: Vec<ServiceTask> = serde_json::from_str(response_string).unwrap();
+        assert_eq!(service_tasks.len(), 1);
+
+        let service_task = &service_tasks[0];
+        assert_eq!(service_task.id(), "f9bb0ecc");
+        assert_eq!(service_task.execution_id(), Some("f9bb0eca"));
+        assert_eq!(service_task.retries(), None);
+        assert_eq!(service_task.tenant_id(), None);
+        assert_eq!(service_task.process_definition_key(), Some("OrderPizza"));
+        assert_eq!(service_task.process_definition_version_tag(), None);
+
+        let variables = service_task.variables();
+        assert_eq!(variables.len(), 2);
+        assert_eq!(variables["pizza_wishlist"].as_str(), Some("5x Vier Jahreszeiten"));
+        assert_eq!(variables["mehrheit_will_pizza"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_parse_task_with_negative_priority() {
+        let response_string = r#"[{"activityId":"ServiceTask_Cleanup","executionId":"a1","id":"t1","processInstanceId":"p1","retries":null,"workerId":"operaton_task_worker","priority":-10,"topicName":"cleanup","businessKey":null,"tenantId":null,"variables":{}}]"#;
+        let service_tasks: Vec<ServiceTask> = serde_json::from_str(response_string).unwrap();
+        assert_eq!(service_tasks[0].priority(), -10);
+    }
+}
@@ -0,0 +1,178 @@
+//! This module includes the TLS configuration of the HTTP client
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+use std::path::Path;
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+use log::{info, warn};
+use reqwest::ClientBuilder;
+
+use crate::error::OperatonError;
+use crate::settings::ConfigParams;
+
+/// Without a TLS backend, the client only supports plain HTTP, so TLS settings are rejected instead of being ignored
+#[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+pub(crate) fn configure_tls(builder: ClientBuilder, config: &ConfigParams) -> Result<ClientBuilder, OperatonError> {
+    if config.tls_ca_bundle().is_some() || config.tls_client_cert().is_some() || config.tls_insecure_skip_verify() {
+        return Err(OperatonError::Configuration(
+            "The TLS settings require the `native-tls` or `rustls-tls` feature".to_string(),
+        ));
+    }
+    Ok(builder)
+}
+
+/// Apply the TLS settings of the configuration to an HTTP client builder
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+pub(crate) fn configure_tls(mut builder: ClientBuilder, config: &ConfigParams) -> Result<ClientBuilder, OperatonError> {
+    #[cfg(feature = "rustls-tls")]
+    {
+        builder = builder.use_rustls_tls();
+    }
+
+    if let Some(ca_bundle) = config.tls_ca_bundle() {
+        let pem = read_file(ca_bundle, "CA bundle")?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|err| invalid(ca_bundle, "CA bundle", err))?;
+        if certificates.is_empty() {
+            return Err(OperatonError::Configuration(format!(
+                "The CA bundle '{}' does not contain any certificate",
+                ca_bundle.display()
+            )));
+        }
+        info!("Trust {} additional CA certificates from '{}'", certificates.len(), ca_bundle.display());
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if let Some(client_cert) = config.tls_client_cert() {
+        builder = builder.identity(client_identity(client_cert, config)?);
+        info!("Use client certificate '{}' for mutual TLS", client_cert.display());
+    }
+
+    if config.tls_insecure_skip_verify() {
+        warn!("TLS certificate verification is disabled. Never use this setting in production.");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    Ok(builder)
+}
+
+/// Load the client identity from a PKCS#12 archive (`.p12`/`.pfx`) or from a PEM certificate and key
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn client_identity(client_cert: &Path, config: &ConfigParams) -> Result<reqwest::Identity, OperatonError> {
+    let cert = read_file(client_cert, "client certificate")?;
+
+    if is_pkcs12(client_cert) {
+        return pkcs12_identity(client_cert, &cert, config.tls_client_cert_password().unwrap_or_default());
+    }
+
+    let key = match config.tls_client_key() {
+        Some(client_key) => read_file(client_key, "client key")?,
+        None => {
+            return Err(OperatonError::Configuration(format!(
+                "The PEM client certificate '{}' requires a client key",
+                client_cert.display()
+            )));
+        }
+    };
+
+    pem_identity(client_cert, &cert, &key)
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn is_pkcs12(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("p12") || extension.eq_ignore_ascii_case("pfx"))
+}
+
+#[cfg(feature = "rustls-tls")]
+fn pem_identity(client_cert: &Path, cert: &[u8], key: &[u8]) -> Result<reqwest::Identity, OperatonError> {
+    let mut pem = cert.to_vec();
+    pem.push(b'\n');
+    pem.extend_from_slice(key);
+    reqwest::Identity::from_pem(&pem).map_err(|err| invalid(client_cert, "client certificate", err))
+}
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+fn pem_identity(client_cert: &Path, cert: &[u8], key: &[u8]) -> Result<reqwest::Identity, OperatonError> {
+    reqwest::Identity::from_pkcs8_pem(cert, key).map_err(|err| invalid(client_cert, "client certificate", err))
+}
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+fn pkcs12_identity(client_cert: &Path, der: &[u8], password: &str) -> Result<reqwest::Identity, OperatonError> {
+    reqwest::Identity::from_pkcs12_der(der, password).map_err(|err| invalid(client_cert, "client certificate", err))
+}
+
+#[cfg(feature = "rustls-tls")]
+fn pkcs12_identity(client_cert: &Path, _der: &[u8], _password: &str) -> Result<reqwest::Identity, OperatonError> {
+    Err(OperatonError::Configuration(format!(
+        "The PKCS#12 client certificate '{}' requires the `native-tls` feature without `rustls-tls`; use a PEM certificate and key instead",
+        client_cert.display()
+    )))
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn read_file(path: &Path, what: &str) -> Result<Vec<u8>, OperatonError> {
+    std::fs::read(path).map_err(|err| {
+        OperatonError::Configuration(format!("Could not read the {what} '{}': {err}", path.display()))
+    })
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn invalid(path: &Path, what: &str, err: reqwest::Error) -> OperatonError {
+    OperatonError::Configuration(format!("Invalid {what} '{}': {err}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_ca_bundle() {
+        let config = ConfigParams::default().with_tls_ca_bundle(Some("/does/not/exist.pem".into()));
+        let err = configure_tls(reqwest::Client::builder(), &config).unwrap_err();
+        assert!(matches!(err, OperatonError::Configuration(_)));
+    }
+
+    #[test]
+    fn test_ca_bundle_without_certificates() {
+        let path = std::env::temp_dir().join(format!("operaton-task-worker-empty-ca-{}.pem", std::process::id()));
+        std::fs::write(&path, b"no certificates in here").unwrap();
+
+        let config = ConfigParams::default().with_tls_ca_bundle(Some(path.clone()));
+        let err = configure_tls(reqwest::Client::builder(), &config).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, OperatonError::Configuration(_)));
+    }
+
+    #[test]
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    fn test_pem_client_certificate_requires_key() {
+        let path = std::env::temp_dir().join(format!("operaton-task-worker-client-{}.pem", std::process::id()));
+        std::fs::write(&path, b"").unwrap();
+
+        let config = ConfigParams::default().with_tls_client_cert(Some(path.clone()), None, None);
+        let err = configure_tls(reqwest::Client::builder(), &config).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.to_string().contains("requires a client key"));
+    }
+
+    #[test]
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    fn test_insecure_skip_verify() {
+        let config = ConfigParams::default().with_tls_insecure_skip_verify(true);
+        assert!(configure_tls(reqwest::Client::builder(), &config).unwrap().build().is_ok());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+    fn test_tls_settings_require_a_tls_backend() {
+        assert!(configure_tls(reqwest::Client::builder(), &ConfigParams::default()).is_ok());
+
+        let config = ConfigParams::default().with_tls_insecure_skip_verify(true);
+        let err = configure_tls(reqwest::Client::builder(), &config).unwrap_err();
+        assert!(err.to_string().contains("`native-tls` or `rustls-tls`"));
+    }
+}
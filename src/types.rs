@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
-use crate::structures::process_variables::ProcessInstanceVariable;
+use base64::Engine as _;
 
-#[derive(serde::Serialize, Debug, Clone)]
+use crate::context::TaskContext;
+use crate::structures::process_variables::{ProcessInstanceVariable, DATE_FORMAT};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OutVariable {
     #[serde(rename = "value")]
@@ -11,17 +14,61 @@ pub struct OutVariable {
     pub typ: String,
     #[serde(rename = "valueInfo")]
     pub value_info: std::collections::HashMap<String, serde_json::Value>,
+    /// Whether the variable is set in the scope of the task's execution when the task is completed,
+    /// instead of being propagated to the process instance
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub local: bool,
+}
+
+impl OutVariable {
+    /// A transient variable is available in the process until the next wait state, but is not persisted
+    pub fn with_transient(self, transient: bool) -> Self {
+        let mut cloned_self = self.clone();
+        if transient {
+            cloned_self.value_info.insert("transient".to_string(), serde_json::Value::Bool(true));
+        } else {
+            cloned_self.value_info.remove("transient");
+        }
+        cloned_self
+    }
+
+    /// Set the variable locally in the task's execution when the task is completed
+    pub fn with_local(self, local: bool) -> Self {
+        let mut cloned_self = self.clone();
+        cloned_self.local = local;
+        cloned_self
+    }
 }
 
 pub type InputVariables = HashMap<String, ProcessInstanceVariable>;
 pub type OutputVariables = HashMap<String, OutVariable>;
 pub type ExternalTaskFn = fn(&InputVariables) -> Result<OutputVariables, Box<dyn std::error::Error>>;
+/// A handler which also takes the [TaskContext] of its task
+pub type ContextTaskFn = fn(&InputVariables, &TaskContext) -> Result<OutputVariables, Box<dyn std::error::Error>>;
+
+/// The function of a registered handler, with or without the task context argument
+#[derive(Clone, Copy, Debug)]
+pub enum HandlerFn {
+    Plain(ExternalTaskFn),
+    WithContext(ContextTaskFn),
+}
+
+impl HandlerFn {
+    /// Run the handler, passing the task context only to handlers which take it
+    pub fn call(&self, input: &InputVariables, context: &TaskContext) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+        match self {
+            HandlerFn::Plain(func) => func(input),
+            HandlerFn::WithContext(func) => func(input, context),
+        }
+    }
+}
 
 pub fn out_string(value: impl Into<String>) -> OutVariable {
     OutVariable {
         value: serde_json::Value::String(value.into()),
         typ: "String".to_string(),
         value_info: std::collections::HashMap::new(),
+        local: false,
     }
 }
 
@@ -31,6 +78,7 @@ pub fn out_bool(value: bool) -> OutVariable {
         value: serde_json::Value::Bool(value),
         typ: "Boolean".to_string(),
         value_info: std::collections::HashMap::new(),
+        local: false,
     }
 }
 
@@ -40,6 +88,7 @@ pub fn out_integer(value: i32) -> OutVariable {
         value: serde_json::Value::Number(serde_json::Number::from(value)),
         typ: "Integer".to_string(),
         value_info: std::collections::HashMap::new(),
+        local: false,
     }
 }
 
@@ -49,6 +98,7 @@ pub fn out_long(value: i64) -> OutVariable {
         value: serde_json::Value::Number(serde_json::Number::from(value)),
         typ: "Long".to_string(),
         value_info: std::collections::HashMap::new(),
+        local: false,
     }
 }
 
@@ -58,6 +108,65 @@ pub fn out_double(value: f64) -> OutVariable {
         value: serde_json::json!(value),
         typ: "Double".to_string(),
         value_info: std::collections::HashMap::new(),
+        local: false,
+    }
+}
+
+#[allow(dead_code)]
+pub fn out_short(value: i16) -> OutVariable {
+    OutVariable {
+        value: serde_json::Value::Number(serde_json::Number::from(value)),
+        typ: "Short".to_string(),
+        value_info: std::collections::HashMap::new(),
+        local: false,
+    }
+}
+
+/// A Date variable, sent in the date format of the REST API
+pub fn out_date<Tz: chrono::TimeZone>(value: &chrono::DateTime<Tz>) -> OutVariable
+where
+    Tz::Offset: std::fmt::Display,
+{
+    OutVariable {
+        value: serde_json::Value::String(value.format(DATE_FORMAT).to_string()),
+        typ: "Date".to_string(),
+        value_info: std::collections::HashMap::new(),
+        local: false,
+    }
+}
+
+/// A variable with the value `null`
+pub fn out_null() -> OutVariable {
+    OutVariable {
+        value: serde_json::Value::Null,
+        typ: "Null".to_string(),
+        value_info: std::collections::HashMap::new(),
+        local: false,
+    }
+}
+
+/// A File variable. The content is sent Base64 encoded.
+pub fn out_file(filename: impl Into<String>, mime_type: Option<&str>, content: &[u8]) -> OutVariable {
+    let mut value_info = std::collections::HashMap::new();
+    value_info.insert("filename".to_string(), serde_json::Value::String(filename.into()));
+    if let Some(mime_type) = mime_type {
+        value_info.insert("mimeType".to_string(), serde_json::Value::String(mime_type.to_string()));
+    }
+    OutVariable {
+        value: serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(content)),
+        typ: "File".to_string(),
+        value_info,
+        local: false,
+    }
+}
+
+/// A Bytes variable. The content is sent Base64 encoded.
+pub fn out_bytes(content: &[u8]) -> OutVariable {
+    OutVariable {
+        value: serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(content)),
+        typ: "Bytes".to_string(),
+        value_info: std::collections::HashMap::new(),
+        local: false,
     }
 }
 
@@ -72,6 +181,7 @@ pub fn out_json(value: &serde_json::Value) -> OutVariable {
         value: serde_json::Value::String(value.to_string()),
         typ: "Json".to_string(),
         value_info,
+        local: false,
     }
 }
 
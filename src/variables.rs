@@ -0,0 +1,417 @@
+//! This module includes the conversion between the variables of a task and typed structs
+
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::structures::process_variables::ProcessInstanceVariable;
+use crate::types::{out_bool, out_date, out_double, out_integer, out_json, out_long, out_null, out_short, out_string};
+use crate::types::{InputVariables, OutVariable, OutputVariables};
+
+pub use operaton_task_worker_macros::{FromVariables, IntoVariables};
+
+/// A struct which can be built from the input variables of a task. Use `#[derive(FromVariables)]` to implement it:
+///
+/// ```ignore
+/// use operaton_task_worker::variables::{FromVariables, Json};
+///
+/// #[derive(FromVariables)]
+/// struct InvoiceInput {
+///     #[variable(rename = "invoiceId")]
+///     invoice_id: String,
+///     amount: f64,
+///     #[variable(default)]
+///     reminders: i32,
+///     approver: Option<String>,
+///     #[variable(json)]
+///     items: Vec<Item>,
+/// }
+///
+/// let input = InvoiceInput::from_variables(variables)?;
+/// ```
+///
+/// Field attributes:
+/// - `rename = "..."`: the name of the variable, which defaults to the field name
+/// - `default` or `default = "path::to::function"`: the value if the variable is missing or `null`
+/// - `json`: deserialize the field with serde from a Json variable
+///
+/// Fields of type `Option` are `None` if the variable is missing or `null`.
+pub trait FromVariables: Sized {
+    fn from_variables(input: &InputVariables) -> Result<Self, VariablesError>;
+}
+
+/// A type which can be read from a single process variable
+pub trait FromVariable: Sized {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem>;
+}
+
+/// A struct which can be converted into the output variables of a task. Use `#[derive(IntoVariables)]` to implement it:
+///
+/// ```ignore
+/// use operaton_task_worker::variables::IntoVariables;
+///
+/// #[derive(IntoVariables)]
+/// struct InvoiceResult {
+///     #[variable(rename = "invoiceNumber")]
+///     invoice_number: String,
+///     total: f64,
+///     #[variable(skip_none)]
+///     discount: Option<f64>,
+///     #[variable(transient)]
+///     pdf_url: String,
+///     #[variable(local)]
+///     reviewer: String,
+///     #[variable(json)]
+///     items: Vec<Item>,
+/// }
+///
+/// return Ok(result.into_variables()?);
+/// ```
+///
+/// Field attributes:
+/// - `rename = "..."`: the name of the variable, which defaults to the field name
+/// - `skip_none`: do not set the variable if an `Option` field is `None`, instead of setting it to `null`
+/// - `transient`: the variable is not persisted
+/// - `local`: the variable is set in the scope of the task's execution
+/// - `json`: serialize the field with serde into a Json variable
+///
+/// The type of a variable follows the field type: `i8`, `u8` and `i16` are Short, `u16` and `i32` are Integer,
+/// the other integers are Long, floats are Double and chrono's `DateTime` is Date, as is `time::OffsetDateTime` with the
+/// `time` feature.
+pub trait IntoVariables {
+    fn into_variables(self) -> Result<OutputVariables, VariablesError>;
+}
+
+/// A type which can be converted into a single process variable
+pub trait IntoVariable {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem>;
+}
+
+/// A field which is (de)serialized with serde as Json variable
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Json<T>(pub T);
+
+/// Why a variable could not be converted
+#[derive(Clone, Debug, PartialEq)]
+pub enum VariableProblem {
+    /// The variable does not exist
+    Missing,
+    /// The variable is `null`, but a value is required
+    Null,
+    /// The variable has another type than the field
+    WrongType { expected: &'static str, found: &'static str },
+    /// The variable has the right type, but its value cannot be converted
+    Invalid(String),
+}
+
+/// A variable which could not be converted, with the reason
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableError {
+    pub name: String,
+    pub problem: VariableProblem,
+}
+
+/// The error of [FromVariables::from_variables] and [IntoVariables::into_variables], which lists every variable
+/// that could not be converted.
+/// Returned from a handler, it is reported as failure of the task.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VariablesError {
+    pub errors: Vec<VariableError>,
+}
+
+impl fmt::Display for VariableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            VariableProblem::Missing => write!(f, "`{}` is missing", self.name),
+            VariableProblem::Null => write!(f, "`{}` is null", self.name),
+            VariableProblem::WrongType { expected, found } => {
+                write!(f, "`{}` has the type {} instead of {}", self.name, found, expected)
+            }
+            VariableProblem::Invalid(message) => write!(f, "`{}` is invalid: {}", self.name, message),
+        }
+    }
+}
+
+impl fmt::Display for VariablesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid variables: ")?;
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VariablesError {}
+
+/// Read a required variable. Problems are added to `errors`.
+pub fn variable<T: FromVariable>(input: &InputVariables, name: &str, errors: &mut Vec<VariableError>) -> Option<T> {
+    let result = match input.get(name) {
+        None => Err(VariableProblem::Missing),
+        Some(variable) if variable.is_null() => Err(VariableProblem::Null),
+        Some(variable) => T::from_variable(variable),
+    };
+    result.map_err(|problem| errors.push(VariableError { name: name.to_string(), problem })).ok()
+}
+
+/// Read an optional variable, which is `None` if it is missing or `null`. Other problems are added to `errors`.
+pub fn optional_variable<T: FromVariable>(input: &InputVariables, name: &str, errors: &mut Vec<VariableError>) -> Option<T> {
+    match input.get(name) {
+        Some(variable) if !variable.is_null() => {
+            T::from_variable(variable).map_err(|problem| errors.push(VariableError { name: name.to_string(), problem })).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Convert a value into a variable and insert it. Problems are added to `errors`.
+pub fn insert_variable<T: IntoVariable>(
+    variables: &mut OutputVariables,
+    errors: &mut Vec<VariableError>,
+    name: &str,
+    value: T,
+    transient: bool,
+    local: bool,
+) {
+    match value.into_variable() {
+        Ok(variable) => {
+            variables.insert(name.to_string(), variable.with_transient(transient).with_local(local));
+        }
+        Err(problem) => errors.push(VariableError { name: name.to_string(), problem }),
+    }
+}
+
+fn wrong_type(expected: &'static str, variable: &ProcessInstanceVariable) -> VariableProblem {
+    VariableProblem::WrongType { expected, found: variable.type_name() }
+}
+
+impl FromVariable for String {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_str().map(str::to_string).ok_or_else(|| wrong_type("String", variable))
+    }
+}
+
+impl FromVariable for bool {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_bool().ok_or_else(|| wrong_type("Boolean", variable))
+    }
+}
+
+impl FromVariable for i16 {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        match variable {
+            ProcessInstanceVariable::Short(short) => Ok(short.value),
+            _ => Err(wrong_type("Short", variable)),
+        }
+    }
+}
+
+impl FromVariable for i32 {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_i32().ok_or_else(|| wrong_type("Integer", variable))
+    }
+}
+
+impl FromVariable for i64 {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_i64().ok_or_else(|| wrong_type("Long", variable))
+    }
+}
+
+impl FromVariable for f64 {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_f64().ok_or_else(|| wrong_type("Double", variable))
+    }
+}
+
+impl FromVariable for DateTime<FixedOffset> {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_date().ok_or_else(|| wrong_type("Date", variable))
+    }
+}
+
+impl FromVariable for DateTime<Utc> {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        DateTime::<FixedOffset>::from_variable(variable).map(|date| date.with_timezone(&Utc))
+    }
+}
+
+#[cfg(feature = "time")]
+impl FromVariable for time::OffsetDateTime {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        let date = DateTime::<FixedOffset>::from_variable(variable)?;
+        let invalid = |err: &dyn fmt::Display| VariableProblem::Invalid(err.to_string());
+        let nanos = date.timestamp_nanos_opt().ok_or_else(|| invalid(&"the date is out of range"))?;
+        let offset = time::UtcOffset::from_whole_seconds(date.offset().local_minus_utc()).map_err(|err| invalid(&err))?;
+        time::OffsetDateTime::from_unix_timestamp_nanos(nanos as i128)
+            .map(|date| date.to_offset(offset))
+            .map_err(|err| invalid(&err))
+    }
+}
+
+impl FromVariable for serde_json::Value {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        variable.as_json().cloned().ok_or_else(|| wrong_type("Json", variable))
+    }
+}
+
+impl<T: DeserializeOwned> FromVariable for Json<T> {
+    fn from_variable(variable: &ProcessInstanceVariable) -> Result<Self, VariableProblem> {
+        let value = serde_json::Value::from_variable(variable)?;
+        serde_json::from_value(value).map(Json).map_err(|err| VariableProblem::Invalid(err.to_string()))
+    }
+}
+
+macro_rules! into_variable {
+    ($constructor:ident: $($ty:ty),*) => {
+        $(impl IntoVariable for $ty {
+            fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+                Ok($constructor(self.into()))
+            }
+        })*
+    };
+}
+
+into_variable!(out_short: i8, u8, i16);
+into_variable!(out_integer: u16, i32);
+into_variable!(out_long: u32, i64);
+into_variable!(out_double: f32, f64);
+into_variable!(out_bool: bool);
+
+impl IntoVariable for String {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        Ok(out_string(self))
+    }
+}
+
+impl IntoVariable for &str {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        Ok(out_string(self))
+    }
+}
+
+/// Integers which do not fit into a Long
+macro_rules! into_long_variable {
+    ($($ty:ty),*) => {
+        $(impl IntoVariable for $ty {
+            fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+                i64::try_from(self)
+                    .map(out_long)
+                    .map_err(|_| VariableProblem::Invalid(format!("{} does not fit into a Long", self)))
+            }
+        })*
+    };
+}
+
+into_long_variable!(u64, i128, u128, isize, usize);
+
+impl<Tz: TimeZone> IntoVariable for DateTime<Tz>
+where
+    Tz::Offset: fmt::Display,
+{
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        Ok(out_date(&self))
+    }
+}
+
+#[cfg(feature = "time")]
+impl IntoVariable for time::OffsetDateTime {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        let offset = FixedOffset::east_opt(self.offset().whole_seconds())
+            .ok_or_else(|| VariableProblem::Invalid(format!("invalid UTC offset {}", self.offset())))?;
+        let date = DateTime::from_timestamp(self.unix_timestamp(), self.nanosecond())
+            .ok_or_else(|| VariableProblem::Invalid(format!("{} is out of range", self)))?;
+        Ok(out_date(&date.with_timezone(&offset)))
+    }
+}
+
+impl IntoVariable for serde_json::Value {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        Ok(out_json(&self))
+    }
+}
+
+impl<T: Serialize> IntoVariable for Json<T> {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        let value = serde_json::to_value(&self.0).map_err(|err| VariableProblem::Invalid(err.to_string()))?;
+        Ok(out_json(&value))
+    }
+}
+
+impl IntoVariable for OutVariable {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        Ok(self)
+    }
+}
+
+/// `None` is a Null variable
+impl<T: IntoVariable> IntoVariable for Option<T> {
+    fn into_variable(self) -> Result<OutVariable, VariableProblem> {
+        self.map_or_else(|| Ok(out_null()), T::into_variable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::structures::process_variables::parse_process_instance_variables;
+
+    #[test]
+    fn test_all_problems_are_reported() {
+        let input = parse_process_instance_variables(
+            r#"{"amount":{"type":"String","value":"12","valueInfo":{}},"approver":{"type":"Null","value":null,"valueInfo":{}}}"#,
+        );
+        let mut errors = Vec::new();
+
+        assert_eq!(variable::<f64>(&input, "amount", &mut errors), None);
+        assert_eq!(variable::<String>(&input, "invoiceId", &mut errors), None);
+        assert_eq!(variable::<String>(&input, "approver", &mut errors), None);
+        assert_eq!(optional_variable::<String>(&input, "approver", &mut errors), None);
+
+        assert_eq!(
+            VariablesError { errors }.to_string(),
+            "Invalid variables: `amount` has the type String instead of Double, `invoiceId` is missing, \
+             `approver` is null"
+        );
+    }
+
+    #[test]
+    fn test_variable_types() {
+        let mut variables = OutputVariables::new();
+        let mut errors = Vec::new();
+
+        insert_variable(&mut variables, &mut errors, "count", 7u8, false, false);
+        insert_variable(&mut variables, &mut errors, "amount", 120, true, false);
+        insert_variable(&mut variables, &mut errors, "total", 5_000_000_000u64, false, true);
+        insert_variable(&mut variables, &mut errors, "approver", None::<String>, false, false);
+        insert_variable(&mut variables, &mut errors, "overflow", u64::MAX, false, false);
+
+        assert_eq!(variables["count"].typ, "Short");
+        assert_eq!(variables["amount"].typ, "Integer");
+        assert_eq!(variables["amount"].value_info["transient"], serde_json::json!(true));
+        assert_eq!(variables["total"].typ, "Long");
+        assert!(variables["total"].local);
+        assert_eq!(variables["approver"].typ, "Null");
+        assert!(!variables.contains_key("overflow"));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn test_offset_date_time_round_trip() {
+        let offset = time::UtcOffset::from_hms(1, 0, 0).unwrap();
+        let date = time::OffsetDateTime::from_unix_timestamp(1_763_629_200).unwrap().to_offset(offset);
+
+        let variable = date.into_variable().unwrap();
+        assert_eq!(variable.typ, "Date");
+        assert_eq!(variable.value, serde_json::json!("2025-11-20T10:00:00.000+0100"));
+
+        let input = parse_process_instance_variables(r#"{"dueDate":{"type":"Date","value":"2025-11-20T10:00:00.000+0100","valueInfo":{}}}"#);
+        assert_eq!(time::OffsetDateTime::from_variable(&input["dueDate"]), Ok(date));
+    }
+}
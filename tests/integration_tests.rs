@@ -1,6 +1,7 @@
 //! The macro tests are located in this file.
 
 use operaton_task_worker_macros::task_handler;
+use operaton_task_worker::context::TaskContext;
 use operaton_task_worker::types::{InputVariables, OutputVariables, out_string, out_json};
 
 
@@ -29,4 +30,294 @@ pub fn get_scanned_files(_input: &InputVariables) -> Result<OutputVariables, Box
     let mut out: OutputVariables = std::collections::HashMap::new();
     out.insert("FILENAMES".to_string(), out_string("TEST"));
     Ok(out)
-}
\ No newline at end of file
+}
+
+#[task_handler(name = "ServiceTask_SendInvoice", topic = "invoicing", lock_duration = 120000)]
+pub fn send_invoice(_input: &InputVariables) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+    Ok(std::collections::HashMap::new())
+}
+
+#[task_handler(name = "ServiceTask_CheckStock", topic = "stock", variables = ["articleId", "quantity"], local_variables = true, max_retries = 3, retry_backoff = "exponential", retry_delay = 1000)]
+pub fn check_stock(_input: &InputVariables) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+    Ok(std::collections::HashMap::new())
+}
+
+#[task_handler(name = "ServiceTask_TenantGreeting")]
+pub fn tenant_greeting(_input: &InputVariables, context: &TaskContext) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+    let mut out: OutputVariables = std::collections::HashMap::new();
+    out.insert("tenant".to_string(), out_string(context.tenant_id().unwrap_or("none")));
+    Ok(out)
+}
+
+#[task_handler(name = "ServiceTask_Archive", topic = "archive", process_definition_keys = ["Invoice"], process_variables(region = "EU", priority = 1))]
+pub fn archive_invoice(_input: &InputVariables) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+    Ok(std::collections::HashMap::new())
+}
+
+#[task_handler(name = "ServiceTask_Archive", topic = "archive", process_definition_keys = ["Order"], process_definition_version_tag = "2.0", process_variables(region = "EU"))]
+pub fn archive_order(_input: &InputVariables) -> Result<OutputVariables, Box<dyn std::error::Error>> {
+    Ok(std::collections::HashMap::new())
+}
+
+fn task_context(tenant_id: &str) -> TaskContext {
+    let task = serde_json::from_value(serde_json::json!({
+        "id": "task-1",
+        "activityId": "ServiceTask_TenantGreeting",
+        "processInstanceId": "instance-1",
+        "topicName": "ServiceTask_TenantGreeting",
+        "priority": 0,
+        "businessKey": null,
+        "workerId": "operaton_task_worker",
+        "tenantId": tenant_id
+    }))
+    .unwrap();
+    TaskContext::new(task)
+}
+
+#[test]
+fn test_handlers_receive_the_task_context() {
+    use operaton_task_worker::registry;
+
+    let input = InputVariables::new();
+    let context = task_context("tenant-a");
+    assert_eq!(context.tenant_id(), Some("tenant-a"));
+
+    let handler = registry::find("ServiceTask_TenantGreeting").unwrap();
+    let output = handler.call(&input, &context).unwrap();
+    assert_eq!(output["tenant"].value, serde_json::json!("tenant-a"));
+
+    // Handlers without the context argument are registered as well
+    let handler = registry::find("example_echo").unwrap();
+    assert!(matches!(handler, operaton_task_worker::types::HandlerFn::Plain(_)));
+    assert!(handler.call(&input, &context).unwrap().contains_key("workerResponse"));
+}
+
+#[test]
+fn test_handlers_are_registered_with_topics() {
+    use operaton_task_worker::registry;
+
+    let topics = registry::all_topics();
+    assert!(topics.contains(&"example_echo"));
+    assert!(topics.contains(&"invoicing"));
+    assert!(!topics.contains(&"ServiceTask_SendInvoice"));
+
+    assert!(registry::find_for_task("ServiceTask_SendInvoice", "invoicing").is_some());
+    assert!(registry::find_for_task("SomeActivity", "example_echo").is_some());
+    assert!(registry::find_for_task("SomeActivity", "unknown_topic").is_none());
+
+    assert_eq!(registry::lock_duration_for_topic("invoicing"), Some(120_000));
+    assert_eq!(registry::lock_duration_for_topic("example_echo"), None);
+}
+
+#[test]
+fn test_handlers_are_registered_with_variables() {
+    use operaton_task_worker::registry;
+
+    assert_eq!(
+        registry::variables_for_topic("stock"),
+        Some(vec!["articleId".to_string(), "quantity".to_string()])
+    );
+    assert!(registry::local_variables_for_topic("stock"));
+
+    assert_eq!(registry::variables_for_topic("example_echo"), None);
+    assert!(!registry::local_variables_for_topic("example_echo"));
+}
+
+#[test]
+fn test_handlers_are_registered_with_retry_policy() {
+    use operaton_task_worker::registry;
+    use operaton_task_worker::retry::{Backoff, RetryPolicyOverride};
+
+    let handler = registry::find_handler_for_task("ServiceTask_CheckStock", "stock").unwrap();
+    assert_eq!(handler.retry_policy, RetryPolicyOverride {
+        backoff: Some(Backoff::Exponential),
+        max_retries: Some(3),
+        delay: Some(1000),
+        max_delay: None,
+    });
+
+    let handler = registry::find_handler_for_task("example_echo", "example_echo").unwrap();
+    assert_eq!(handler.retry_policy, RetryPolicyOverride::NONE);
+}
+
+#[test]
+fn test_handlers_are_registered_with_filters() {
+    use operaton_task_worker::registry;
+    use operaton_task_worker::structures::service_task::ServiceTask;
+
+    // The registration order of handlers is not defined
+    let mut filter = registry::subscription_filter_for_topic("archive");
+    filter.process_definition_key_in.sort();
+    assert_eq!(filter.process_definition_key_in, ["Invoice".to_string(), "Order".to_string()]);
+    assert_eq!(filter.process_definition_version_tag, None);
+    assert_eq!(filter.process_variables.len(), 1);
+    assert_eq!(filter.process_variables["region"], serde_json::json!("EU"));
+    assert!(registry::subscription_filter_for_topic("example_echo").process_definition_key_in.is_empty());
+
+    let task = |process_definition_key: &str, version_tag: Option<&str>| -> ServiceTask {
+        serde_json::from_value(serde_json::json!({
+            "id": "task-1",
+            "activityId": "ServiceTask_Archive",
+            "processInstanceId": "instance-1",
+            "processDefinitionKey": process_definition_key,
+            "processDefinitionVersionTag": version_tag,
+            "topicName": "archive",
+            "priority": 0,
+            "businessKey": null,
+            "workerId": "operaton_task_worker"
+        }))
+        .unwrap()
+    };
+
+    let handler = registry::find_handler_for_service_task(&task("Invoice", None)).unwrap();
+    assert_eq!(handler.filter.process_variables, &[("region", "\"EU\""), ("priority", "1")]);
+    assert!(registry::find_handler_for_service_task(&task("Order", Some("2.0"))).is_some());
+    assert!(registry::find_handler_for_service_task(&task("Order", Some("1.0"))).is_none());
+    assert!(registry::find_handler_for_service_task(&task("Shipping", None)).is_none());
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Item {
+    sku: String,
+    quantity: u32,
+}
+
+fn default_currency() -> String {
+    "EUR".to_string()
+}
+
+#[derive(Debug, PartialEq, operaton_task_worker::variables::FromVariables)]
+struct InvoiceInput {
+    #[variable(rename = "invoiceId")]
+    invoice_id: String,
+    amount: f64,
+    #[variable(default)]
+    reminders: i32,
+    #[variable(default = "default_currency")]
+    currency: String,
+    approver: Option<String>,
+    #[variable(json)]
+    items: Vec<Item>,
+}
+
+#[test]
+fn test_derive_from_variables() {
+    use operaton_task_worker::structures::process_variables::parse_process_instance_variables;
+    use operaton_task_worker::variables::FromVariables;
+
+    let input = parse_process_instance_variables(r#"{
+        "invoiceId": {"type":"String","value":"INV-1","valueInfo":{}},
+        "amount": {"type":"Integer","value":120,"valueInfo":{}},
+        "approver": {"type":"Null","value":null,"valueInfo":{}},
+        "items": {"type":"Json","value":"[{\"sku\":\"A-1\",\"quantity\":2}]","valueInfo":{}}
+    }"#);
+
+    assert_eq!(
+        InvoiceInput::from_variables(&input).unwrap(),
+        InvoiceInput {
+            invoice_id: "INV-1".to_string(),
+            amount: 120.0,
+            reminders: 0,
+            currency: "EUR".to_string(),
+            approver: None,
+            items: vec![Item { sku: "A-1".to_string(), quantity: 2 }],
+        }
+    );
+
+    let input = parse_process_instance_variables(r#"{
+        "amount": {"type":"String","value":"120","valueInfo":{}},
+        "reminders": {"type":"Boolean","value":true,"valueInfo":{}},
+        "items": {"type":"Json","value":"[{\"sku\":\"A-1\"}]","valueInfo":{}}
+    }"#);
+
+    let err = InvoiceInput::from_variables(&input).unwrap_err();
+    let names: Vec<&str> = err.errors.iter().map(|error| error.name.as_str()).collect();
+    assert_eq!(names, ["invoiceId", "amount", "reminders", "items"]);
+    assert!(err.to_string().contains("`amount` has the type String instead of Double"));
+}
+
+#[derive(serde::Serialize)]
+struct Position {
+    sku: String,
+    price: f64,
+}
+
+#[derive(operaton_task_worker::variables::IntoVariables)]
+struct InvoiceResult {
+    #[variable(rename = "invoiceNumber")]
+    invoice_number: String,
+    total: f64,
+    reminders: i16,
+    #[variable(skip_none)]
+    discount: Option<f64>,
+    approver: Option<String>,
+    #[variable(transient)]
+    pdf_url: String,
+    #[variable(local)]
+    reviewer: String,
+    #[variable(json)]
+    positions: Vec<Position>,
+}
+
+#[test]
+fn test_derive_into_variables() {
+    use operaton_task_worker::variables::IntoVariables;
+
+    let result = InvoiceResult {
+        invoice_number: "INV-1".to_string(),
+        total: 99.5,
+        reminders: 2,
+        discount: None,
+        approver: None,
+        pdf_url: "https://example.com/invoice.pdf".to_string(),
+        reviewer: "jane".to_string(),
+        positions: vec![Position { sku: "A-1".to_string(), price: 99.5 }],
+    };
+    let variables = result.into_variables().unwrap();
+
+    assert_eq!(variables["invoiceNumber"].typ, "String");
+    assert_eq!(variables["total"].typ, "Double");
+    assert_eq!(variables["reminders"].typ, "Short");
+    assert!(!variables.contains_key("discount"));
+    assert_eq!(variables["approver"].typ, "Null");
+    assert_eq!(variables["pdf_url"].value_info["transient"], serde_json::json!(true));
+    assert!(variables["reviewer"].local);
+    assert_eq!(variables["positions"].typ, "Json");
+    let positions: serde_json::Value = serde_json::from_str(variables["positions"].value.as_str().unwrap()).unwrap();
+    assert_eq!(positions, serde_json::json!([{ "sku": "A-1", "price": 99.5 }]));
+}
+
+// Field names which are also used by the generated code must neither break compilation nor read the wrong data
+#[derive(Debug, PartialEq, operaton_task_worker::variables::FromVariables, operaton_task_worker::variables::IntoVariables)]
+struct MacroLocals {
+    input: String,
+    errors: i32,
+    variables: bool,
+    value: Option<String>,
+}
+
+#[test]
+fn test_derive_with_field_names_of_generated_locals() {
+    use operaton_task_worker::structures::process_variables::parse_process_instance_variables;
+    use operaton_task_worker::variables::{FromVariables, IntoVariables};
+
+    let input = parse_process_instance_variables(r#"{
+        "input": {"type":"String","value":"scan.pdf","valueInfo":{}},
+        "errors": {"type":"Integer","value":2,"valueInfo":{}},
+        "variables": {"type":"Boolean","value":true,"valueInfo":{}},
+        "value": {"type":"String","value":"42","valueInfo":{}}
+    }"#);
+
+    let locals = MacroLocals::from_variables(&input).unwrap();
+    assert_eq!(
+        locals,
+        MacroLocals { input: "scan.pdf".to_string(), errors: 2, variables: true, value: Some("42".to_string()) }
+    );
+
+    let variables = locals.into_variables().unwrap();
+    assert_eq!(variables.len(), 4);
+    assert_eq!(variables["input"].value, serde_json::json!("scan.pdf"));
+    assert_eq!(variables["errors"].value, serde_json::json!(2));
+    assert_eq!(variables["variables"].value, serde_json::json!(true));
+    assert_eq!(variables["value"].value, serde_json::json!("42"));
+}